@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+/// How much time a player gets to think.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimeControl {
+    /// `total` for the whole game.
+    SuddenDeath { total: Duration },
+    /// `total` to start with and `increment` more after every move.
+    Increment {
+        total: Duration,
+        increment: Duration,
+    },
+    /// `per_move` for every move. Time left over is not carried to the next move.
+    PerMove { per_move: Duration },
+}
+
+/// The time a player has left.
+///
+/// Agents are not interrupted: the time they took is charged when they answer, and an
+/// agent that answers after its time ran out loses the game.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Clock {
+    control: TimeControl,
+    remaining: Duration,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        let remaining = match control {
+            TimeControl::SuddenDeath { total } | TimeControl::Increment { total, .. } => total,
+            TimeControl::PerMove { per_move } => per_move,
+        };
+
+        Self { control, remaining }
+    }
+
+    #[inline(always)]
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    /// The time left as of the start of the current turn.
+    #[inline(always)]
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    /// Charges `elapsed` to the clock, returns whether the time ran out.
+    pub(super) fn charge(&mut self, elapsed: Duration) -> bool {
+        match self.remaining.checked_sub(elapsed) {
+            Some(remaining) => {
+                self.remaining = remaining;
+                false
+            }
+            None => {
+                self.remaining = Duration::ZERO;
+                true
+            }
+        }
+    }
+
+    /// Credits the time for the next move once a move was made.
+    pub(super) fn moved(&mut self) {
+        match self.control {
+            TimeControl::SuddenDeath { .. } => {}
+            TimeControl::Increment { increment, .. } => self.remaining += increment,
+            TimeControl::PerMove { per_move } => self.remaining = per_move,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn sudden_death_is_never_credited() {
+        let mut clock = Clock::new(TimeControl::SuddenDeath { total: 3 * SECOND });
+
+        assert!(!clock.charge(SECOND));
+        clock.moved();
+        assert_eq!(clock.remaining(), 2 * SECOND);
+
+        assert!(!clock.charge(2 * SECOND));
+        assert_eq!(clock.remaining(), Duration::ZERO);
+        assert!(clock.charge(SECOND));
+        assert_eq!(clock.remaining(), Duration::ZERO);
+    }
+
+    #[test]
+    fn increment_is_credited_after_every_move() {
+        let mut clock = Clock::new(TimeControl::Increment {
+            total: 3 * SECOND,
+            increment: SECOND,
+        });
+
+        assert!(!clock.charge(2 * SECOND));
+        clock.moved();
+        assert_eq!(clock.remaining(), 2 * SECOND);
+
+        assert!(clock.charge(3 * SECOND));
+    }
+
+    #[test]
+    fn per_move_time_is_not_carried_over() {
+        let mut clock = Clock::new(TimeControl::PerMove {
+            per_move: 2 * SECOND,
+        });
+
+        assert!(!clock.charge(SECOND));
+        assert_eq!(clock.remaining(), SECOND);
+        clock.moved();
+        assert_eq!(clock.remaining(), 2 * SECOND);
+
+        assert!(clock.charge(3 * SECOND));
+    }
+}
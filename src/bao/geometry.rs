@@ -0,0 +1,144 @@
+use super::Direction;
+
+use std::ops::Range;
+
+/// The most bowls a player can have, the size of the boards of a `Side`.
+pub const MAX_BOWLS: usize = 32;
+
+/// The bowls of one player's half of the board.
+///
+/// A player's bowls form a ring: the outer row is indexed from left to right, the row in
+/// front of it from right to left, so sowing CCW walks the ring in increasing order. The
+/// last row is the inner row, which faces the opponent's inner row.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Geometry {
+    bowls_per_row: u8,
+    rows: u8,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self::new(8, 2)
+    }
+}
+
+impl Geometry {
+    pub fn new(bowls_per_row: usize, rows: usize) -> Self {
+        assert!((1..=2).contains(&rows), "A player has one or two rows");
+        assert!(bowls_per_row >= 2, "A row has at least two bowls");
+        assert!(
+            rows * bowls_per_row <= MAX_BOWLS,
+            "A player has at most {} bowls",
+            MAX_BOWLS
+        );
+        // all stones of both players must fit into a single bowl
+        assert!(
+            4 * rows * bowls_per_row <= u8::MAX as usize,
+            "Too many bowls for the stones to fit into a bowl"
+        );
+
+        Self {
+            bowls_per_row: bowls_per_row as u8,
+            rows: rows as u8,
+        }
+    }
+
+    #[inline(always)]
+    pub fn bowls_per_row(&self) -> usize {
+        self.bowls_per_row as usize
+    }
+
+    #[inline(always)]
+    pub fn rows(&self) -> usize {
+        self.rows as usize
+    }
+
+    /// The bowls of one player.
+    #[inline(always)]
+    pub fn bowls(&self) -> usize {
+        self.rows() * self.bowls_per_row()
+    }
+
+    #[inline(always)]
+    pub fn inner_row(&self) -> Range<usize> {
+        (self.bowls() - self.bowls_per_row())..self.bowls()
+    }
+
+    #[inline(always)]
+    pub fn is_inner(&self, index: usize) -> bool {
+        self.inner_row().contains(&index)
+    }
+
+    /// The bowls of a row in the order they are seen from the bottom of the board. The
+    /// player on top sees the board turned around.
+    pub fn row(&self, row: usize, on_top: bool) -> Vec<usize> {
+        let mut indices =
+            (row * self.bowls_per_row()..(row + 1) * self.bowls_per_row()).collect::<Vec<_>>();
+        if (row % 2 == 1) != on_top {
+            indices.reverse();
+        }
+        indices
+    }
+
+    #[inline(always)]
+    pub fn next_index(&self, index: usize, direction: Direction) -> usize {
+        match direction {
+            Direction::CW => {
+                if index == 0 {
+                    self.bowls() - 1
+                } else {
+                    index - 1
+                }
+            }
+
+            Direction::CCW => {
+                if index == self.bowls() - 1 {
+                    0
+                } else {
+                    index + 1
+                }
+            }
+        }
+    }
+
+    /// The bowl at the same place of the row with left and right swapped. Sowing the
+    /// mirrored bowls CW walks the ring like sowing the bowls CCW.
+    #[inline(always)]
+    pub fn mirror(&self, index: usize) -> usize {
+        let row = index / self.bowls_per_row();
+        (2 * row + 1) * self.bowls_per_row() - 1 - index
+    }
+
+    /// The opponent's inner row bowl facing an inner row bowl.
+    #[inline(always)]
+    pub fn opposite(&self, index: usize) -> usize {
+        (2 * self.rows() - 1) * self.bowls_per_row() - 1 - index
+    }
+
+    /// The bowl behind an inner row bowl, if there is more than one row.
+    #[inline(always)]
+    pub fn behind(&self, index: usize) -> Option<usize> {
+        if self.rows > 1 {
+            Some(2 * self.inner_row().start - 1 - index)
+        } else {
+            None
+        }
+    }
+
+    /// The end bowl of the inner row that sowing along the inner row in `direction` starts
+    /// from.
+    #[inline(always)]
+    pub fn kichwa(&self, direction: Direction) -> usize {
+        match direction {
+            Direction::CW => self.inner_row().end - 1,
+            Direction::CCW => self.inner_row().start,
+        }
+    }
+
+    /// The nyumba, the bowl right of the middle of the inner row as seen by its player:
+    /// the fifth of eight.
+    #[inline(always)]
+    pub fn nyumba(&self) -> usize {
+        self.inner_row().end - 1 - self.bowls_per_row() / 2
+    }
+}
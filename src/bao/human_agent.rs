@@ -1,4 +1,4 @@
-use super::{Agent, Game, Turn::*};
+use super::{Action, Agent, Direction, Game, Move, MoveKind, Side, Turn::*};
 
 use std::io;
 
@@ -11,105 +11,188 @@ impl Default for HumanAgent {
 }
 
 impl Agent for HumanAgent {
-    fn pick_index(&mut self, game: &Game) -> usize {
+    fn pick_move(&mut self, game: &Game) -> Move {
+        match self.read_action(game, false) {
+            Action::Move(mv) => mv,
+            Action::Resign => unreachable!("resigning is not offered"),
+        }
+    }
+
+    fn act(&mut self, game: &Game) -> Action {
+        self.read_action(game, true)
+    }
+}
+
+impl HumanAgent {
+    /// Reads a move, or a resignation if `can_resign`, from stdin.
+    fn read_action(&mut self, game: &Game, can_resign: bool) -> Action {
         game.print_board();
 
-        let player = if game.turn() == Player1 {
-            &game.player1
+        let player = game.current_player();
+
+        let directions = game.directions();
+        let geometry = game.geometry();
+
+        let mtaji_moves = game
+            .legal_moves_iter()
+            .filter(|&mv| game.move_kind(mv) == Some(MoveKind::Mtaji))
+            .map(|mv| format!("{} {:?}", mv.index, mv.direction))
+            .collect::<Vec<_>>();
+
+        if mtaji_moves.is_empty() {
+            println!("No move steals (takasa).");
         } else {
-            &game.player2
-        };
+            println!("Moves that steal (mtaji): {}", mtaji_moves.join(", "));
+        }
+
+        let or_resign = if can_resign { " or resign" } else { "" };
 
         loop {
-            println!("{}, enter bowl index: ", player.name);
+            if directions.len() > 1 {
+                println!(
+                    "{}, enter bowl index and direction (cw/ccw){}: ",
+                    player.name, or_resign
+                );
+            } else {
+                println!("{}, enter bowl index{}: ", player.name, or_resign);
+            }
             let mut input_text = String::new();
 
             io::stdin()
                 .read_line(&mut input_text)
                 .expect("failed to read from stdin");
 
-            let trimmed = input_text.trim();
+            if can_resign && input_text.trim().eq_ignore_ascii_case("resign") {
+                return Action::Resign;
+            }
 
-            let index = match trimmed.parse::<usize>() {
-                Ok(i) => i,
-                Err(_) => {
-                    println!("Please enter a valid index between 0 and 15.");
+            let mut words = input_text.split_whitespace();
+
+            let index = match words.next().map(str::parse::<usize>) {
+                Some(Ok(i)) if i < geometry.bowls() => i,
+                _ => {
+                    println!(
+                        "Please enter a valid index between 0 and {}.",
+                        geometry.bowls() - 1
+                    );
+                    continue;
+                }
+            };
+
+            let direction = match words.next().map(str::to_lowercase).as_deref() {
+                None if directions.len() == 1 => directions[0],
+                Some("cw") => Direction::CW,
+                Some("ccw") => Direction::CCW,
+                _ => {
+                    println!("Please enter a direction, either cw or ccw.");
                     continue;
                 }
             };
 
-            if !player.is_valid_index(index) {
-                println!("Bowl must contain at least 2 stones.");
+            if !directions.contains(&direction) {
+                println!("This game is only sown {:?}.", directions[0]);
+                continue;
+            }
+
+            let mv = Move::new(index, direction);
+
+            if !game.is_valid_move(mv) {
+                println!(
+                    "{}",
+                    game.rules().illegal_reason(game.position(), directions, mv)
+                );
                 continue;
             }
 
-            return index;
+            return Action::Move(mv);
         }
     }
 }
 
 impl Game {
     fn print_board(&self) {
+        let geometry = self.geometry();
+        let line = "-".repeat(5 * geometry.bowls_per_row() + 1);
+        let indent = " ".repeat((line.len() / 2).saturating_sub(9));
+
         println!(
-            "           {:2}Player 2{:2}",
+            "{}{:2}Player 2{:2}",
+            indent,
             if self.turn() == Player2 { "->" } else { "" },
             if self.turn() == Player2 { "<-" } else { "" },
         );
-        print!("|");
-        for i in (0..8).rev() {
-            print!(" {:2} |", i);
+        for row in 0..geometry.rows() {
+            let bowls = geometry.row(row, true);
+            if row > 0 {
+                println!("{}", line);
+            }
+            print_indices(&bowls);
+            println!("{}", line);
+            print_bowls(self.position.side(Player2), &bowls);
+            if row + 1 < geometry.rows() {
+                println!();
+            }
         }
-        println!();
-        println!("-----------------------------------------");
-        print!("|");
-        for i in (0..8).rev() {
-            print!(" {:2} |", self.player2.board_half[i]);
+        print_stones(self.position.side(Player2), self.rules.has_stores());
+        println!("{} Round: {}", "=".repeat(line.len() + 11), self.turn_count);
+        for row in (0..geometry.rows()).rev() {
+            let bowls = geometry.row(row, false);
+            print_bowls(self.position.side(Player1), &bowls);
+            if row == geometry.rows() - 1 {
+                print_stones(self.position.side(Player1), self.rules.has_stores());
+            } else {
+                println!();
+            }
+            println!("{}", line);
+            print_indices(&bowls);
+            if row > 0 {
+                println!("{}", line);
+            }
         }
-        println!();
+        println!(
+            "{}{:2}Player 1{:2}",
+            indent,
+            if self.turn() == Player1 { "->" } else { "" },
+            if self.turn() == Player1 { "<-" } else { "" },
+        );
+    }
+}
 
-        println!("-----------------------------------------");
-        print!("|");
-        for i in 8..16 {
-            print!(" {:2} |", i);
-        }
-        println!();
-        println!("-----------------------------------------");
-        print!("|");
-        for i in 8..16 {
-            print!(" {:2} |", self.player2.board_half[i]);
-        }
-        println!(" Stones: {}", self.player2.board_half.iter().sum::<u8>());
+fn print_indices(bowls: &[usize]) {
+    print!("|");
+    for i in bowls {
+        print!(" {:2} |", i);
+    }
+    println!();
+}
+
+fn print_bowls(side: &Side, bowls: &[usize]) {
+    print!("|");
+    for &i in bowls {
+        print_bowl(side, i);
+    }
+}
+
+fn print_bowl(side: &Side, index: usize) {
+    if index == side.geometry.nyumba() && side.has_nyumba() {
+        print!("[{:2}]|", side.board()[index]);
+    } else {
+        print!(" {:2} |", side.board()[index]);
+    }
+}
+
+fn print_stones(side: &Side, captured: bool) {
+    if captured {
         println!(
-            "==================================================== Round: {}",
-            self.turn_count
+            " Stones: {} Captured: {}",
+            side.board().iter().sum::<u8>(),
+            side.captured()
         );
-        print!("|");
-        for i in (8..16).rev() {
-            print!(" {:2} |", self.player1.board_half[i]);
-        }
-        println!(" Stones: {}", self.player1.board_half.iter().sum::<u8>());
-        println!("-----------------------------------------");
-        print!("|");
-        for i in (8..16).rev() {
-            print!(" {:2} |", i);
-        }
-        println!();
-        println!("-----------------------------------------");
-        print!("|");
-        for i in 0..8 {
-            print!(" {:2} |", self.player1.board_half[i]);
-        }
-        println!();
-        println!("-----------------------------------------");
-        print!("|");
-        for i in 0..8 {
-            print!(" {:2} |", i);
-        }
-        println!();
+    } else {
         println!(
-            "           {:2}Player 1{:2}",
-            if self.turn() == Player1 { "->" } else { "" },
-            if self.turn() == Player1 { "<-" } else { "" },
+            " Stones: {} Store: {}",
+            side.board().iter().sum::<u8>(),
+            side.store()
         );
     }
 }
@@ -0,0 +1,89 @@
+use super::relay;
+use super::zobrist::RulesKey;
+use super::{
+    Direction, Geometry, Limits, Move, MoveKind, MoveOutcome, Position, RuleSet, Side, SowEvent,
+    Turn,
+};
+
+/// The stones of every bowl at the start.
+const START_STONES: u8 = 2;
+
+/// The rules of Hus, as played in Namibia on four rows of eight.
+///
+/// Every move is sown in the direction the game was created with, from a bowl with at
+/// least two stones. A lap ending in an occupied bowl goes on from there. If that bowl is
+/// in the inner row and the opponent's inner row bowl facing it holds stones, it is
+/// captured together with the bowl behind it and the stones are sown from the bowl the
+/// move started from. A player who can't move has lost.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Hus {
+    pub limits: Limits,
+}
+
+impl Hus {
+    /// Whether a lap ending in `index` with `stones` in it captures.
+    #[inline(always)]
+    fn captures(&self, opponent: &Side, index: usize, stones: u8) -> bool {
+        let geometry = self.geometry();
+
+        stones >= 2 && geometry.is_inner(index) && opponent.board_half[geometry.opposite(index)] > 0
+    }
+}
+
+impl RuleSet for Hus {
+    fn geometry(&self) -> Geometry {
+        Geometry::default()
+    }
+
+    /// Two stones in every bowl.
+    fn setup(&self) -> Position {
+        let side = Side::new(self.geometry(), START_STONES);
+
+        Position::new([side, side])
+    }
+
+    fn mirror_symmetric(&self) -> bool {
+        true
+    }
+
+    fn is_legal_move(&self, position: &Position, _directions: &[Direction], mv: Move) -> bool {
+        position.current().board_half[mv.index] >= 2
+    }
+
+    fn illegal_reason(&self, _position: &Position, _directions: &[Direction], _mv: Move) -> String {
+        String::from("Bowl must contain at least 2 stones.")
+    }
+
+    fn move_kind(&self, position: &Position, mv: Move) -> MoveKind {
+        relay::move_kind(position, mv, |opponent, index, stones| {
+            self.captures(opponent, index, stones)
+        })
+    }
+
+    fn sow(
+        &self,
+        position: &mut Position,
+        mv: Move,
+        trace: &mut Option<&mut Vec<SowEvent>>,
+    ) -> MoveOutcome {
+        relay::sow(
+            position,
+            mv,
+            self.limits.lap_limit,
+            |opponent, index, stones| self.captures(opponent, index, stones),
+            trace,
+        )
+    }
+
+    fn has_lost(&self, position: &Position, _directions: &[Direction], player: Turn) -> bool {
+        relay::has_lost(position.side(player))
+    }
+
+    fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    fn key(&self) -> u64 {
+        RulesKey::new("hus").with_limits(self.limits).finish()
+    }
+}
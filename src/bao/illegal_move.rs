@@ -0,0 +1,77 @@
+use super::{InvariantError, Move, Position, Turn};
+
+use std::error::Error;
+use std::fmt;
+
+/// A move the player to move may not play.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IllegalMove {
+    /// The position the move was tried in.
+    pub position: Box<Position>,
+    /// The seat of the player who tried it.
+    pub seat: Turn,
+    pub mv: Move,
+}
+
+impl fmt::Display for IllegalMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} can't play {:?} in {:?}",
+            self.seat, self.mv, self.position
+        )
+    }
+}
+
+impl Error for IllegalMove {}
+
+/// Why a move could not be made.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MoveError {
+    Illegal(IllegalMove),
+    /// The move broke an invariant, only found with the `validate` feature.
+    Invariant(InvariantError),
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::Illegal(error) => error.fmt(f),
+            MoveError::Invariant(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for MoveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MoveError::Illegal(error) => Some(error),
+            MoveError::Invariant(error) => Some(error),
+        }
+    }
+}
+
+impl From<IllegalMove> for MoveError {
+    fn from(error: IllegalMove) -> Self {
+        MoveError::Illegal(error)
+    }
+}
+
+impl From<InvariantError> for MoveError {
+    fn from(error: InvariantError) -> Self {
+        MoveError::Invariant(error)
+    }
+}
+
+/// What `Game::play` does when an agent picks an illegal move.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum IllegalMovePolicy {
+    /// The agent loses the game.
+    #[default]
+    Forfeit,
+    /// The agent is asked for another move, up to `attempts` times, and then loses the
+    /// game.
+    AskAgain { attempts: usize },
+    /// The game is given up and `play` returns the illegal move.
+    Abort,
+}
@@ -0,0 +1,241 @@
+use super::{Game, Move, MoveOutcome, MoveResult, Position, SowEvent, Turn, Turn::*};
+
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+
+/// An invariant a move broke.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// The stones in play changed, though only a move that decided the game may leave
+    /// stones in hand.
+    Conservation { before: usize, after: usize },
+    /// A bowl holds more stones than there are in play.
+    BowlLimit {
+        player: Turn,
+        index: usize,
+        stones: u8,
+    },
+    /// The player to move after the move isn't the one the move passed the turn to.
+    Turn { expected: Turn, found: Turn },
+    /// Sowing overflowed a bowl or a count of stones.
+    Overflow,
+    /// The Zobrist key doesn't match the bowls, a bowl was changed without updating it.
+    Key,
+}
+
+/// A move that broke an invariant, with the position it was played in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvariantError {
+    pub violation: Violation,
+    pub position: Box<Position>,
+    pub mv: Move,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Conservation { before, after } => {
+                write!(f, "{} stones in play became {}", before, after)
+            }
+            Violation::BowlLimit {
+                player,
+                index,
+                stones,
+            } => write!(f, "Bowl {} of {:?} holds {} stones", index, player, stones),
+            Violation::Turn { expected, found } => {
+                write!(f, "{:?} is to move instead of {:?}", found, expected)
+            }
+            Violation::Overflow => write!(f, "Sowing overflowed"),
+            Violation::Key => write!(f, "The key is out of date"),
+        }
+    }
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} after {:?} in {:?}",
+            self.violation, self.mv, self.position
+        )
+    }
+}
+
+impl Error for InvariantError {}
+
+thread_local! {
+    /// Whether a count of stones overflowed during the move being validated.
+    static OVERFLOWED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Adds counts of stones. With the `validate` feature an overflow saturates and the move
+/// is reported as broken once it is done.
+#[inline(always)]
+pub(super) fn add_stones(stones: u8, more: u8) -> u8 {
+    if cfg!(feature = "validate") {
+        stones.checked_add(more).unwrap_or_else(|| {
+            OVERFLOWED.with(|overflowed| overflowed.set(true));
+            u8::MAX
+        })
+    } else {
+        stones + more
+    }
+}
+
+impl Position {
+    /// Checks that a move played in this position kept the stones in play, left no bowl
+    /// with more stones than that, passed the turn as its `outcome` tells and kept the key
+    /// up to date. `after` is the position the move led to.
+    pub fn check_move(&self, after: &Position, outcome: &MoveOutcome) -> Result<(), Violation> {
+        let stones = self.side(Player1).stones() + self.side(Player2).stones();
+        let stones_after = after.side(Player1).stones() + after.side(Player2).stones();
+        if stones_after != stones && outcome.result == MoveResult::Ongoing {
+            return Err(Violation::Conservation {
+                before: stones,
+                after: stones_after,
+            });
+        }
+
+        for player in [Player1, Player2] {
+            let board = after.side(player).board();
+            if let Some(index) = (0..board.len()).find(|&index| board[index] as usize > stones) {
+                return Err(Violation::BowlLimit {
+                    player,
+                    index,
+                    stones: board[index],
+                });
+            }
+        }
+
+        let expected = if outcome.extra_turn {
+            self.turn
+        } else {
+            self.turn.other()
+        };
+        if after.turn != expected {
+            return Err(Violation::Turn {
+                expected,
+                found: after.turn,
+            });
+        }
+
+        let mut rekeyed = *after;
+        rekeyed.rekey();
+        if rekeyed.key() != after.key() {
+            return Err(Violation::Key);
+        }
+
+        Ok(())
+    }
+}
+
+impl Game {
+    /// Plays `mv` like `make_move`, but leaves the game as it was and returns the position
+    /// and the move if the move broke an invariant. Used with the `validate` feature.
+    pub(super) fn play_validated(
+        &mut self,
+        mv: Move,
+        trace: Option<&mut Vec<SowEvent>>,
+    ) -> Result<MoveOutcome, InvariantError> {
+        let before = self.position;
+
+        OVERFLOWED.with(|overflowed| overflowed.set(false));
+        let outcome = self.position.play(&*self.rules, mv, trace);
+        let checked = if OVERFLOWED.with(Cell::get) {
+            Err(Violation::Overflow)
+        } else {
+            before.check_move(&self.position, &outcome)
+        };
+
+        checked.map(|()| outcome).map_err(|violation| {
+            self.position = before;
+            InvariantError {
+                violation,
+                position: Box::new(before),
+                mv,
+            }
+        })
+    }
+}
+
+#[cfg(all(test, feature = "validate"))]
+mod tests {
+    use super::super::{
+        Direction, Game, Geometry, Move, MoveError, MoveKind, MoveOutcome, MoveResult, Player,
+        Position, RuleSet, Side, SowEvent, Turn,
+    };
+    use super::Violation;
+
+    /// Rules whose only move drops more stones into a bowl than it can hold.
+    #[derive(Debug)]
+    struct Overflowing;
+
+    impl RuleSet for Overflowing {
+        fn geometry(&self) -> Geometry {
+            Geometry::new(2, 1)
+        }
+
+        fn setup(&self) -> Position {
+            let side = Side::new(self.geometry(), 1);
+            Position::new([side, side])
+        }
+
+        fn is_legal_move(
+            &self,
+            _position: &Position,
+            _directions: &[Direction],
+            _mv: Move,
+        ) -> bool {
+            true
+        }
+
+        fn sow(
+            &self,
+            position: &mut Position,
+            mv: Move,
+            _trace: &mut Option<&mut Vec<SowEvent>>,
+        ) -> MoveOutcome {
+            let (player, _) = position.sides_mut();
+            player.add(mv.index, u8::MAX);
+
+            MoveOutcome {
+                result: MoveResult::Ongoing,
+                kind: MoveKind::Takasa,
+                steal: 0,
+                laps: 1,
+                extra_turn: false,
+            }
+        }
+
+        fn has_lost(&self, _position: &Position, _directions: &[Direction], _player: Turn) -> bool {
+            false
+        }
+
+        fn key(&self) -> u64 {
+            0
+        }
+    }
+
+    #[test]
+    fn overflow_is_reported_and_the_move_not_made() {
+        let mut game = Game::new(
+            Direction::CCW,
+            Overflowing,
+            Player::new("Player 1", 0),
+            Player::new("Player 2", 1),
+        );
+        let before = *game.position();
+
+        match game.make_move(Move::new(0, Direction::CCW)) {
+            Err(MoveError::Invariant(error)) => {
+                assert_eq!(error.violation, Violation::Overflow);
+                assert_eq!(*error.position, before);
+            }
+            made => panic!("overflow not reported: {:?}", made),
+        }
+        assert_eq!(*game.position(), before);
+        assert_eq!(game.turn_count(), 1);
+        assert_eq!(game.moves().count(), 0);
+    }
+}
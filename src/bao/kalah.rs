@@ -0,0 +1,319 @@
+use super::invariant::add_stones;
+use super::trace::{record, SowEvent};
+use super::zobrist::RulesKey;
+use super::{
+    Direction, Geometry, Limits, Move, MoveKind, MoveOutcome, MoveResult, Position, RuleSet,
+    SetupError, Side, TieBreak, Turn,
+};
+
+use std::cmp::Ordering;
+
+/// The rules of Kalah, the game of most game-AI papers, with a row of pits and a store
+/// for each player.
+///
+/// A move sows all stones of a pit, one by one in the direction the game was created
+/// with, through the player's own store and the opponent's pits, skipping the opponent's
+/// store. A move whose last stone lands in the own store earns another turn. If it lands
+/// in an empty pit of the player and the opponent's pit facing it holds stones, both are
+/// captured into the store. Once either row is empty, each player captures the stones left
+/// in their own row and whoever has more stones in the store wins.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Kalah {
+    /// The pits of a row, Kalah(6, 4) has six.
+    pub pits: usize,
+    /// The stones in each pit at the start, Kalah(6, 4) has four. All stones of the set
+    /// must fit into a store.
+    pub stones: u8,
+    pub limits: Limits,
+}
+
+impl Default for Kalah {
+    fn default() -> Self {
+        Self {
+            pits: 6,
+            stones: 4,
+            limits: Limits {
+                tie_break: TieBreak::Stones,
+                ..Limits::default()
+            },
+        }
+    }
+}
+
+/// A place a stone can be sown into, seen from the player to move.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Pit {
+    Own(usize),
+    Store,
+    Opponent(usize),
+}
+
+impl Kalah {
+    /// The place after `pit`, the opponent's store is left out.
+    #[inline(always)]
+    fn next(&self, pit: Pit, direction: Direction) -> Pit {
+        let last = self.pits - 1;
+
+        match (pit, direction) {
+            (Pit::Own(index), Direction::CCW) if index == last => Pit::Store,
+            (Pit::Own(0), Direction::CW) => Pit::Store,
+            (Pit::Store, Direction::CCW) => Pit::Opponent(0),
+            (Pit::Store, Direction::CW) => Pit::Opponent(last),
+            (Pit::Opponent(index), Direction::CCW) if index == last => Pit::Own(0),
+            (Pit::Opponent(0), Direction::CW) => Pit::Own(last),
+            (Pit::Own(index), _) => Pit::Own(self.geometry().next_index(index, direction)),
+            (Pit::Opponent(index), _) => {
+                Pit::Opponent(self.geometry().next_index(index, direction))
+            }
+        }
+    }
+}
+
+impl RuleSet for Kalah {
+    fn geometry(&self) -> Geometry {
+        Geometry::new(self.pits, 1)
+    }
+
+    /// Panics unless the rules pass `validate_rules`.
+    fn setup(&self) -> Position {
+        if let Err(error) = self.validate_rules() {
+            panic!("{}", error);
+        }
+
+        let side = Side::new(self.geometry(), self.stones);
+
+        Position::new([side, side])
+    }
+
+    fn mirror_symmetric(&self) -> bool {
+        true
+    }
+
+    fn is_legal_move(&self, position: &Position, _directions: &[Direction], mv: Move) -> bool {
+        position.current().board_half[mv.index] > 0
+    }
+
+    fn illegal_reason(&self, _position: &Position, _directions: &[Direction], _mv: Move) -> String {
+        String::from("Bowl must contain stones.")
+    }
+
+    fn sow(
+        &self,
+        position: &mut Position,
+        mv: Move,
+        trace: &mut Option<&mut Vec<SowEvent>>,
+    ) -> MoveOutcome {
+        let geometry = self.geometry();
+        let (player, opponent) = position.sides_mut();
+
+        let mut outcome = MoveOutcome {
+            result: MoveResult::Ongoing,
+            kind: MoveKind::Takasa,
+            steal: 0,
+            laps: 1,
+            extra_turn: false,
+        };
+
+        let mut hand = player.take(mv.index);
+        record(
+            trace,
+            SowEvent::Pickup {
+                index: mv.index,
+                stones: hand,
+            },
+        );
+
+        let mut pit = Pit::Own(mv.index);
+        while hand > 0 {
+            pit = self.next(pit, mv.direction);
+            hand -= 1;
+
+            match pit {
+                Pit::Own(index) => {
+                    player.add(index, 1);
+                    record(trace, SowEvent::Drop { index });
+                }
+                Pit::Store => {
+                    player.capture(1);
+                    record(trace, SowEvent::Store);
+                }
+                Pit::Opponent(index) => {
+                    opponent.add(index, 1);
+                    record(trace, SowEvent::Feed { index });
+                }
+            }
+        }
+
+        match pit {
+            Pit::Store => outcome.extra_turn = true,
+            Pit::Own(index) if player.board_half[index] == 1 => {
+                let opposite = geometry.opposite(index);
+                if opponent.board_half[opposite] > 0 {
+                    let stones = opponent.take(opposite);
+                    record(
+                        trace,
+                        SowEvent::Capture {
+                            index: opposite,
+                            stones,
+                        },
+                    );
+                    // the last stone goes into the store with the captured ones
+                    let last = player.take(index);
+                    player.capture(add_stones(stones, last));
+                    outcome.kind = MoveKind::Mtaji;
+                    outcome.steal = stones;
+                }
+            }
+            _ => {}
+        }
+
+        if player.has_majority(opponent) {
+            outcome.result = MoveResult::Won;
+        } else if opponent.board().iter().all(|&pit| pit == 0)
+            || player.board().iter().all(|&pit| pit == 0)
+        {
+            player.capture_board();
+            opponent.capture_board();
+
+            outcome.result = match player.captured.cmp(&opponent.captured) {
+                Ordering::Greater => MoveResult::Won,
+                Ordering::Less => MoveResult::Lost,
+                Ordering::Equal => MoveResult::Drawn,
+            };
+        }
+
+        outcome
+    }
+
+    fn has_stores(&self) -> bool {
+        true
+    }
+
+    /// All stones of the set must fit into a store.
+    fn validate_rules(&self) -> Result<(), SetupError> {
+        let stones = 2 * self.pits * self.stones as usize;
+        if stones > u8::MAX as usize {
+            return Err(SetupError::SetTooLarge(stones));
+        }
+
+        Ok(())
+    }
+
+    /// A player has lost once the opponent has more than half of the stones in the store,
+    /// or once either row is empty and the stones left on the board leave them behind.
+    fn has_lost(&self, position: &Position, _directions: &[Direction], player: Turn) -> bool {
+        let (side, opponent) = (position.side(player), position.side(player.other()));
+        if opponent.has_majority(side) {
+            return true;
+        }
+
+        let empty = |side: &Side| side.board().iter().all(|&pit| pit == 0);
+        (empty(side) || empty(opponent)) && side.stones() < opponent.stones()
+    }
+
+    fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    fn key(&self) -> u64 {
+        RulesKey::new("kalah")
+            .with(self.pits as u64)
+            .with(self.stones as u64)
+            .with_limits(self.limits)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bao::tests::set_up;
+    use crate::bao::{Game, Player, Setup, SetupError, Turn::*};
+
+    /// A game of Kalah(6, 4) sown CCW in which player 1 is to move with `player1`
+    /// against `player2` and the stores holding `stores`.
+    fn game(player1: &[u8], player2: &[u8], stores: [u8; 2]) -> Game {
+        let setup = Setup::new(Direction::CCW, Kalah::default())
+            .captured(Player1, stores[0])
+            .captured(Player2, stores[1]);
+
+        set_up(setup, player1, player2)
+    }
+
+    #[test]
+    fn last_stone_in_the_store_earns_another_turn() {
+        let mut game = game(&[1, 0, 0, 0, 0, 1], &[4; 6], [0, 0]);
+
+        let outcome = game.make_move(Move::new(5, Direction::CCW)).unwrap();
+
+        assert!(outcome.extra_turn);
+        assert_eq!(outcome.result, MoveResult::Ongoing);
+        assert_eq!(game.position().turn(), Player1);
+        assert_eq!(game.position().side(Player1).captured(), 1);
+    }
+
+    #[test]
+    fn last_stone_in_an_empty_pit_captures_the_facing_pit() {
+        let mut game = game(&[1, 0, 0, 2, 0, 0], &[1, 1, 1, 1, 3, 1], [0, 0]);
+
+        let outcome = game.make_move(Move::new(0, Direction::CCW)).unwrap();
+
+        assert!(!outcome.extra_turn);
+        assert_eq!(outcome.kind, MoveKind::Mtaji);
+        assert_eq!(outcome.steal, 3);
+        assert_eq!(game.position().turn(), Player2);
+        // the captured stones and the last stone go into the store
+        assert_eq!(game.position().side(Player1).captured(), 4);
+        assert_eq!(game.position().side(Player1).board(), &[0, 0, 0, 2, 0, 0]);
+        assert_eq!(game.position().side(Player2).board(), &[1, 1, 1, 1, 0, 1]);
+    }
+
+    #[test]
+    fn empty_row_ends_the_game_and_sweeps_the_other() {
+        let mut game = game(&[0, 0, 0, 0, 0, 1], &[4, 4, 0, 0, 0, 0], [5, 2]);
+
+        let outcome = game.make_move(Move::new(5, Direction::CCW)).unwrap();
+
+        assert_eq!(outcome.result, MoveResult::Lost);
+        assert_eq!(game.position().side(Player1).captured(), 6);
+        assert_eq!(game.position().side(Player2).captured(), 10);
+        assert!(game
+            .position()
+            .side(Player2)
+            .board()
+            .iter()
+            .all(|&pit| pit == 0));
+    }
+
+    #[test]
+    fn setup_rejects_a_set_too_large_for_a_store() {
+        let built = Setup::new(
+            Direction::CCW,
+            Kalah {
+                stones: 22,
+                ..Kalah::default()
+            },
+        )
+        .build(Player::new("Player 1", 0), Player::new("Player 2", 1))
+        .map(|_| ());
+
+        assert_eq!(built, Err(SetupError::SetTooLarge(264)));
+    }
+
+    #[test]
+    fn setup_rejects_a_position_with_an_empty_row() {
+        let setup = |player1: &[u8], player2: &[u8]| {
+            Setup::new(Direction::CCW, Kalah::default())
+                .board(Player1, player1)
+                .board(Player2, player2)
+                .build(Player::new("Player 1", 0), Player::new("Player 2", 1))
+                .map(|_| ())
+        };
+
+        assert_eq!(setup(&[0; 6], &[4; 6]), Err(SetupError::GameOver(Player1)));
+        assert_eq!(
+            setup(&[4, 4, 4, 0, 0, 0], &[0; 6]),
+            Err(SetupError::GameOver(Player2))
+        );
+    }
+}
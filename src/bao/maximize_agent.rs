@@ -1,4 +1,4 @@
-use super::{Agent, Game, MoveResult, Turn::*};
+use super::{Agent, Game, InfiniteMove, Move, MoveOutcome, MoveResult, RuleSet};
 
 pub struct MaximizeAgent;
 
@@ -8,35 +8,31 @@ impl Default for MaximizeAgent {
     }
 }
 
-impl Agent for MaximizeAgent {
-    fn pick_index(&mut self, game: &Game) -> usize {
-        let (mut player, mut opponent) = if game.turn() == Player1 {
-            (game.player1.clone(), game.player2.clone())
-        } else {
-            (game.player2.clone(), game.player1.clone())
-        };
+/// How much a move is worth to the player making it.
+fn score(rules: &dyn RuleSet, outcome: MoveOutcome) -> i32 {
+    match (outcome.result, rules.limits().infinite_move) {
+        // always favour the winning move over the max steal move
+        (MoveResult::Won, _) | (MoveResult::Infinite, InfiniteMove::Wins) => i32::MAX,
+        (MoveResult::Lost, _) | (MoveResult::Infinite, InfiniteMove::Loses) => -1,
+        (MoveResult::Drawn, _) | (MoveResult::Infinite, InfiniteMove::Draw) => 0,
+        (MoveResult::Ongoing, _) => outcome.steal as i32,
+    }
+}
 
-        let mut max_steal_index = 0;
-        let mut max_steal = 0;
-        for index in 0..16 {
-            let steal = match Game::steal_dry_run(
-                index,
-                game.direction,
-                game.mode,
-                &mut player,
-                &mut opponent,
-            ) {
-                MoveResult::None(s) => s,
-                MoveResult::Lost(s) => s,
-                MoveResult::Won(s) => s,
-            };
+impl Agent for MaximizeAgent {
+    fn pick_move(&mut self, game: &Game) -> Move {
+        let mut max_steal_move = None;
+        let mut max_steal = i32::MIN;
+        for mv in game.legal_moves_iter() {
+            let (_, outcome) = game.position().apply(game.rules(), mv);
+            let steal = score(game.rules(), outcome);
 
             if steal > max_steal {
                 max_steal = steal;
-                max_steal_index = index;
+                max_steal_move = Some(mv);
             }
         }
 
-        max_steal_index
+        max_steal_move.expect("No valid move?")
     }
 }
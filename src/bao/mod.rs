@@ -1,3 +1,53 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+mod geometry;
+pub use geometry::{Geometry, MAX_BOWLS};
+
+mod rule_set;
+pub use rule_set::RuleSet;
+
+mod rules;
+pub use rules::Bao;
+
+mod relay;
+
+mod omweso;
+pub use omweso::Omweso;
+
+mod hus;
+pub use hus::Hus;
+
+mod oware;
+pub use oware::Oware;
+
+mod kalah;
+pub use kalah::Kalah;
+
+mod position;
+pub use position::{MoveOutcome, MoveResult, Position, Side};
+
+mod trace;
+pub use trace::{MoveTrace, SowEvent};
+
+mod setup;
+pub use setup::{Setup, SetupError};
+
+mod illegal_move;
+pub use illegal_move::{IllegalMove, IllegalMovePolicy, MoveError};
+
+mod clock;
+pub use clock::{Clock, TimeControl};
+
+mod invariant;
+pub use invariant::{InvariantError, Violation};
+
+mod symmetry;
+pub use symmetry::Canonical;
+
+mod zobrist;
+
 mod human_agent;
 pub use human_agent::HumanAgent;
 
@@ -10,72 +60,124 @@ pub use training_radiate_agent::RadiateAgent;
 mod maximize_agent;
 pub use maximize_agent::MaximizeAgent;
 
-#[derive(PartialEq, Eq)]
-enum Turn {
+/// One of the two seats at the board. Player 1 sits at the bottom and moves first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Turn {
     Player1,
     Player2,
 }
 
 use Turn::*;
 
-enum MoveResult {
-    None(u8),
-    Lost(u8),
-    Won(u8),
+impl Turn {
+    #[inline(always)]
+    pub fn other(self) -> Turn {
+        match self {
+            Player1 => Player2,
+            Player2 => Player1,
+        }
+    }
 }
 
-#[allow(unused)]
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[allow(unused, clippy::upper_case_acronyms)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Direction {
     CW,
     CCW,
 }
 
 impl Direction {
+    pub const ALL: [Direction; 2] = [Direction::CW, Direction::CCW];
+
     #[inline(always)]
-    fn input_enc(&self) -> f32 {
+    pub fn reverse(&self) -> Direction {
         match self {
-            Direction::CW => 0.0,
-            Direction::CCW => 1.0,
+            Direction::CW => Direction::CCW,
+            Direction::CCW => Direction::CW,
         }
     }
 }
 
 impl Direction {
     #[inline(always)]
-    fn next_index(&self, index: usize) -> usize {
+    fn input_enc(&self) -> f32 {
         match self {
-            Direction::CW => {
-                if index == 0 {
-                    15
-                } else {
-                    index - 1
-                }
-            }
-
-            Direction::CCW => {
-                if index == 15 {
-                    0
-                } else {
-                    index + 1
-                }
-            }
+            Direction::CW => 0.0,
+            Direction::CCW => 1.0,
         }
     }
 }
 
+/// How a game ends when a move sows more laps than the lap limit allows.
 #[allow(unused)]
-#[derive(Copy, Clone, PartialEq, Eq)]
-pub enum Mode {
-    Normal, // all stones required
-    Easy,   // just the inner row must be empty to win
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InfiniteMove {
+    Loses, // the player who made the move loses
+    Wins,  // the player who made the move wins
+    Draw,
+}
+
+/// How a game ends when the turn limit is reached.
+#[allow(unused)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TieBreak {
+    Draw,
+    Stones, // the player with more stones on the board and in the store wins
+}
+
+/// Laps a single move may sow before it counts as infinite.
+pub const DEFAULT_LAP_LIMIT: usize = 1000;
+
+/// Turns a game may last before it is settled by the tie break.
+pub const DEFAULT_TURN_LIMIT: usize = 1000;
+
+/// How a game that doesn't end by itself is settled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// A move that is still sowing after this many laps never ends, the game is then
+    /// settled by `infinite_move`. Only rules with relay sowing can sow on forever.
+    pub lap_limit: usize,
+    pub infinite_move: InfiniteMove,
+    /// The game is settled by `tie_break` after this many turns.
+    pub turn_limit: usize,
+    pub tie_break: TieBreak,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            lap_limit: DEFAULT_LAP_LIMIT,
+            infinite_move: InfiniteMove::Loses,
+            turn_limit: DEFAULT_TURN_LIMIT,
+            tie_break: TieBreak::Draw,
+        }
+    }
+}
+
+/// A bowl to sow from and the direction to sow in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub index: usize,
+    pub direction: Direction,
+}
+
+impl Move {
+    pub fn new(index: usize, direction: Direction) -> Self {
+        Self { index, direction }
+    }
+}
+
+/// Whether a move steals, in Bao in its first lap.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MoveKind {
+    Mtaji,
+    Takasa,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Player {
     name: String,
     tag: usize,
-    board_half: [u8; 16],
 }
 
 impl Player {
@@ -83,7 +185,6 @@ impl Player {
         Self {
             name: String::from(name),
             tag,
-            board_half: [2; 16],
         }
     }
 
@@ -96,210 +197,681 @@ impl Player {
     pub fn tag(&self) -> usize {
         self.tag
     }
+}
 
-    #[inline(always)]
-    fn is_valid_index(&self, index: usize) -> bool {
-        self.board_half[index] > 1
-    }
-
-    #[inline(always)]
-    fn has_lost(&self, mode: Mode) -> bool {
-        // If there is at most one stone per bowl: lost
-        if self.board_half.iter().all(|&bowl| bowl < 2) {
-            return true;
-        }
+/// What an agent does on its turn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    Move(Move),
+    Resign,
+}
 
-        // If this is easy mode and the inner row is empty: lost
-        if (mode == Mode::Easy) && self.board_half.iter().skip(8).all(|&bowl| bowl == 0) {
-            return true;
-        }
+pub trait Agent {
+    /// `play` only asks while the player to move has a legal move.
+    fn pick_move(&mut self, game: &Game) -> Move;
 
-        // Otherwise: not lost
-        false
+    /// Moves or resigns. Agents that never resign only pick moves.
+    fn act(&mut self, game: &Game) -> Action {
+        Action::Move(self.pick_move(game))
     }
 }
 
-pub trait Agent {
-    fn pick_index(&mut self, game: &Game) -> usize;
+/// Why a game ended.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Reason {
+    /// A move won, lost or drew the game under the rules, or the player to move had no
+    /// legal move.
+    Rules,
+    /// A move sowed more laps than the lap limit allows, settled by
+    /// `Limits::infinite_move`.
+    InfiniteMove,
+    /// The turn limit was reached and the game adjudicated by `Limits::tie_break`.
+    Adjudication,
+    /// The loser resigned.
+    Resignation,
+    /// The loser picked an illegal move.
+    Forfeit,
+    /// The loser ran out of time.
+    Timeout,
+}
+
+pub enum Outcome {
+    Win { winner: Player, loser: Player },
+    Draw { player1: Player, player2: Player },
 }
 
 pub struct GameResult {
-    pub winner: Player,
-    pub loser: Player,
+    pub outcome: Outcome,
+    pub reason: Reason,
     pub turn_count: usize,
+    /// The position the game ended in.
+    pub position: Position,
+    /// Every move of the game and its steps, if the game was traced.
+    pub traces: Option<Vec<MoveTrace>>,
+    /// The illegal move the game was forfeited with.
+    pub illegal_move: Option<IllegalMove>,
+}
+
+impl GameResult {
+    /// The winner, `None` for a draw.
+    pub fn winner(&self) -> Option<&Player> {
+        match &self.outcome {
+            Outcome::Win { winner, .. } => Some(winner),
+            Outcome::Draw { .. } => None,
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct Game {
     direction: Direction,
-    mode: Mode,
+    rules: Arc<dyn RuleSet>,
+    /// The Zobrist key of the rules.
+    rules_key: u64,
+    position: Position,
+    /// The turn being played, counted from 1.
     turn_count: usize,
+    /// The moves played so far and the positions they were played in.
+    history: Vec<(Move, Position)>,
     player1: Player,
     player2: Player,
+    illegal_move_policy: IllegalMovePolicy,
+    /// By seat, `None` if the game is played without time control.
+    clocks: Option<[Clock; 2]>,
 }
 
 impl Game {
-    pub fn new(direction: Direction, mode: Mode, player1: Player, player2: Player) -> Self {
+    pub fn new(
+        direction: Direction,
+        rules: impl RuleSet + 'static,
+        player1: Player,
+        player2: Player,
+    ) -> Self {
+        Self::with_rules(direction, Arc::new(rules), player1, player2)
+    }
+
+    /// Like `new`, for rules that are shared. Panics if the rules fail
+    /// `RuleSet::validate_rules`, `Setup` returns the error instead.
+    pub fn with_rules(
+        direction: Direction,
+        rules: Arc<dyn RuleSet>,
+        player1: Player,
+        player2: Player,
+    ) -> Self {
         Self {
             direction,
-            mode,
+            position: rules.setup(),
+            rules_key: rules.key(),
+            rules,
             turn_count: 1,
+            history: Vec::new(),
             player1,
             player2,
+            illegal_move_policy: IllegalMovePolicy::default(),
+            clocks: None,
         }
     }
 
-    pub fn play<A1: Agent, A2: Agent>(mut self, agent1: &mut A1, agent2: &mut A2) -> GameResult {
-        let (winner, loser) = loop {
-            let move_result = if self.turn() == Player1 {
-                self.make_move(agent1)
-            } else {
-                self.make_move(agent2)
+    /// What `play` does when an agent picks an illegal move, the agent forfeits by
+    /// default.
+    pub fn illegal_move_policy(mut self, policy: IllegalMovePolicy) -> Self {
+        self.illegal_move_policy = policy;
+        self
+    }
+
+    /// Puts both players on a clock with `control`, the game is played without time
+    /// control by default.
+    pub fn time_control(self, control: TimeControl) -> Self {
+        self.time_controls(control, control)
+    }
+
+    /// Like `time_control`, with a different time control for each player.
+    pub fn time_controls(mut self, player1: TimeControl, player2: TimeControl) -> Self {
+        self.clocks = Some([Clock::new(player1), Clock::new(player2)]);
+        self
+    }
+
+    /// Plays the game to the end, or until an agent picks an illegal move and the policy
+    /// is to abort, or a move breaks an invariant under the `validate` feature.
+    pub fn play<A1: Agent, A2: Agent>(
+        self,
+        agent1: &mut A1,
+        agent2: &mut A2,
+    ) -> Result<GameResult, MoveError> {
+        self.play_with(agent1, agent2, None)
+    }
+
+    /// Like `play`, but collects the trace of every move into the result.
+    pub fn play_traced<A1: Agent, A2: Agent>(
+        self,
+        agent1: &mut A1,
+        agent2: &mut A2,
+    ) -> Result<GameResult, MoveError> {
+        self.play_with(agent1, agent2, Some(Vec::new()))
+    }
+
+    fn play_with<A1: Agent, A2: Agent>(
+        mut self,
+        agent1: &mut A1,
+        agent2: &mut A2,
+        mut traces: Option<Vec<MoveTrace>>,
+    ) -> Result<GameResult, MoveError> {
+        let mut illegal_move = None;
+
+        let (winner, reason) = loop {
+            let turn = self.turn();
+
+            // the rules need not end a game whose player to move has no legal move
+            if self.legal_moves_iter().next().is_none() {
+                let lost = |player| {
+                    self.rules
+                        .has_lost(&self.position, self.directions(), player)
+                };
+                let winner = if lost(turn) {
+                    Some(turn.other())
+                } else if lost(turn.other()) {
+                    Some(turn)
+                } else {
+                    None
+                };
+                break (winner, Reason::Rules);
+            }
+
+            let mut rejected = 0;
+            let made = loop {
+                let started = Instant::now();
+                let action = if turn == Player1 {
+                    agent1.act(&self)
+                } else {
+                    agent2.act(&self)
+                };
+
+                if let Some(clocks) = &mut self.clocks {
+                    if clocks[turn as usize].charge(started.elapsed()) {
+                        break Err(Reason::Timeout);
+                    }
+                }
+
+                let mv = match action {
+                    Action::Move(mv) => mv,
+                    Action::Resign => break Err(Reason::Resignation),
+                };
+
+                let made = match &mut traces {
+                    Some(traces) => self.make_move_traced(mv).map(|(outcome, events)| {
+                        traces.push(MoveTrace { mv, events });
+                        outcome
+                    }),
+                    None => self.make_move(mv),
+                };
+
+                match (made, self.illegal_move_policy) {
+                    (Ok(outcome), _) => break Ok(outcome),
+                    (Err(error @ MoveError::Invariant(_)), _) => return Err(error),
+                    (Err(error), IllegalMovePolicy::Abort) => return Err(error),
+                    (Err(_), IllegalMovePolicy::AskAgain { attempts }) if rejected < attempts => {
+                        rejected += 1
+                    }
+                    (Err(MoveError::Illegal(error)), _) => {
+                        illegal_move = Some(error);
+                        break Err(Reason::Forfeit);
+                    }
+                }
             };
 
-            match (move_result, self.turn()) {
-                (MoveResult::Won(_), Player1) | (MoveResult::Lost(_), Player2) => {
-                    break (self.player1, self.player2)
+            let outcome = match made {
+                Ok(outcome) => outcome,
+                Err(reason) => break (Some(turn.other()), reason),
+            };
+
+            if let Some(clocks) = &mut self.clocks {
+                clocks[turn as usize].moved();
+            }
+
+            match (outcome.result, turn) {
+                (MoveResult::Won, Player1) | (MoveResult::Lost, Player2) => {
+                    break (Some(Player1), Reason::Rules)
+                }
+                (MoveResult::Lost, Player1) | (MoveResult::Won, Player2) => {
+                    break (Some(Player2), Reason::Rules)
                 }
-                (MoveResult::Lost(_), Player1) | (MoveResult::Won(_), Player2) => {
-                    break (self.player2, self.player1)
+                (MoveResult::Drawn, _) => break (None, Reason::Rules),
+                (MoveResult::Infinite, turn) => {
+                    let winner = match self.rules.limits().infinite_move {
+                        InfiniteMove::Loses => Some(turn.other()),
+                        InfiniteMove::Wins => Some(turn),
+                        InfiniteMove::Draw => None,
+                    };
+                    break (winner, Reason::InfiniteMove);
                 }
-                _ => self.turn_count += 1,
+                _ => {}
             }
+
+            if self.turn_count > self.rules.limits().turn_limit {
+                break (self.tie_break(), Reason::Adjudication);
+            }
+        };
+
+        let outcome = match winner {
+            Some(Player1) => Outcome::Win {
+                winner: self.player1,
+                loser: self.player2,
+            },
+            Some(Player2) => Outcome::Win {
+                winner: self.player2,
+                loser: self.player1,
+            },
+            None => Outcome::Draw {
+                player1: self.player1,
+                player2: self.player2,
+            },
         };
 
-        GameResult {
-            winner,
-            loser,
-            turn_count: self.turn_count,
+        Ok(GameResult {
+            outcome,
+            reason,
+            turn_count: self.turn_count - 1,
+            position: self.position,
+            traces,
+            illegal_move,
+        })
+    }
+
+    /// The winner of a game that reached the turn limit, `None` for a draw.
+    fn tie_break(&self) -> Option<Turn> {
+        match self.rules.limits().tie_break {
+            TieBreak::Draw => None,
+            TieBreak::Stones => match self
+                .position
+                .side(Player1)
+                .stones()
+                .cmp(&self.position.side(Player2).stones())
+            {
+                Ordering::Greater => Some(Player1),
+                Ordering::Less => Some(Player2),
+                Ordering::Equal => None,
+            },
+        }
+    }
+
+    #[inline(always)]
+    pub fn rules(&self) -> &dyn RuleSet {
+        &*self.rules
+    }
+
+    #[inline(always)]
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// The Zobrist key of the game: the position, the direction of sowing unless each
+    /// move picks its own, and the rules. Games that can go on the same way get the same
+    /// key.
+    #[inline(always)]
+    pub fn key(&self) -> u64 {
+        let direction = if self.rules.direction_choice() {
+            0
+        } else {
+            zobrist::KEYS.directions[self.direction as usize]
+        };
+
+        self.position.key() ^ direction ^ self.rules_key
+    }
+
+    #[inline(always)]
+    pub fn turn_count(&self) -> usize {
+        self.turn_count
+    }
+
+    /// The clock of `seat`, `None` if the game is played without time control.
+    #[inline(always)]
+    pub fn clock(&self, seat: Turn) -> Option<&Clock> {
+        self.clocks.as_ref().map(|clocks| &clocks[seat as usize])
+    }
+
+    /// The time the player to move has left, `None` if the game is played without time
+    /// control.
+    #[inline(always)]
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.clock(self.turn()).map(Clock::remaining)
+    }
+
+    #[inline(always)]
+    pub fn geometry(&self) -> Geometry {
+        self.rules.geometry()
+    }
+
+    /// The player whose turn it is.
+    #[inline(always)]
+    pub fn current_player(&self) -> &Player {
+        if self.turn() == Player1 {
+            &self.player1
+        } else {
+            &self.player2
+        }
+    }
+
+    /// The player waiting for their turn.
+    #[inline(always)]
+    pub fn opponent(&self) -> &Player {
+        if self.turn() == Player1 {
+            &self.player2
+        } else {
+            &self.player1
         }
     }
 
     #[inline(always)]
     fn turn(&self) -> Turn {
-        if (self.turn_count % 2) == 1 {
-            Player1
+        self.position.turn()
+    }
+
+    /// The directions a move may be sown in.
+    #[inline(always)]
+    pub fn directions(&self) -> &[Direction] {
+        if self.rules.direction_choice() {
+            &Direction::ALL
+        } else {
+            std::slice::from_ref(&self.direction)
+        }
+    }
+
+    /// Whether the player to move may play `mv`.
+    pub fn is_valid_move(&self, mv: Move) -> bool {
+        (mv.index < self.geometry().bowls())
+            && self.directions().contains(&mv.direction)
+            && self
+                .rules
+                .is_legal_move(&self.position, self.directions(), mv)
+    }
+
+    /// Every move the player to move may play, ordered by bowl.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.legal_moves_iter().collect()
+    }
+
+    /// The moves of `legal_moves`, without allocating.
+    pub fn legal_moves_iter(&self) -> impl Iterator<Item = Move> + '_ {
+        (0..self.geometry().bowls())
+            .flat_map(move |index| {
+                self.directions()
+                    .iter()
+                    .map(move |&direction| Move::new(index, direction))
+            })
+            .filter(move |&mv| self.is_valid_move(mv))
+    }
+
+    /// Whether `mv` steals, `None` if the player to move can't play it.
+    pub fn move_kind(&self, mv: Move) -> Option<MoveKind> {
+        if self.is_valid_move(mv) {
+            Some(self.rules.move_kind(&self.position, mv))
         } else {
-            Player2
+            None
         }
     }
 
-    fn make_move<A: Agent>(&mut self, agent: &mut A) -> MoveResult {
-        let mut index = agent.pick_index(self);
+    /// Whether the player to move has any move that steals.
+    pub fn has_mtaji_move(&self) -> bool {
+        self.legal_moves_iter()
+            .any(|mv| self.move_kind(mv) == Some(MoveKind::Mtaji))
+    }
 
-        let (mut player, mut opponent) = if self.turn() == Player1 {
-            (&mut self.player1, &mut self.player2)
+    /// Plays `mv` for the player to move and starts the next turn. The move can be taken
+    /// back with `unmake_move`.
+    pub fn make_move(&mut self, mv: Move) -> Result<MoveOutcome, MoveError> {
+        self.make(mv, None)
+    }
+
+    /// Like `make_move`, but also returns every step of the move.
+    pub fn make_move_traced(
+        &mut self,
+        mv: Move,
+    ) -> Result<(MoveOutcome, Vec<SowEvent>), MoveError> {
+        let mut events = Vec::new();
+        let outcome = self.make(mv, Some(&mut events))?;
+        Ok((outcome, events))
+    }
+
+    fn make(
+        &mut self,
+        mv: Move,
+        trace: Option<&mut Vec<SowEvent>>,
+    ) -> Result<MoveOutcome, MoveError> {
+        if !self.is_valid_move(mv) {
+            return Err(MoveError::Illegal(IllegalMove {
+                position: Box::new(self.position),
+                seat: self.turn(),
+                mv,
+            }));
+        }
+
+        let before = self.position;
+        let outcome = if cfg!(feature = "validate") {
+            self.play_validated(mv, trace)?
         } else {
-            (&mut self.player2, &mut self.player1)
+            self.position.play(&*self.rules, mv, trace)
         };
 
-        debug_assert!(
-            (index < 16) && player.is_valid_index(index),
-            "Invalid index"
-        );
+        self.history.push((mv, before));
+        self.turn_count += 1;
 
-        let mut total_steal = 0;
+        Ok(outcome)
+    }
 
-        let mut hand = player.board_half[index];
-        player.board_half[index] = 0;
+    /// Takes back the last move and returns it, `None` if no move has been made.
+    pub fn unmake_move(&mut self) -> Option<Move> {
+        let (mv, position) = self.history.pop()?;
+        self.position = position;
+        self.turn_count -= 1;
 
-        while hand > 0 {
-            index = self.direction.next_index(index);
-            hand -= 1;
-            player.board_half[index] += 1;
+        Some(mv)
+    }
 
-            if hand == 0 && player.board_half[index] >= 2 {
-                hand = player.board_half[index];
-                player.board_half[index] = 0;
+    /// The moves made so far, the first one first.
+    pub fn moves(&self) -> impl Iterator<Item = Move> + '_ {
+        self.history.iter().map(|&(mv, _)| mv)
+    }
+}
 
-                // steal from opponent
-                if (8..=15).contains(&index) {
-                    let opponent_index = (15 - index) + 8;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    /// Every ruleset, and Bao with the rules of Bao la Kiswahili switched on.
+    fn all_rules() -> Vec<Arc<dyn RuleSet>> {
+        vec![
+            Arc::new(Bao::easy()),
+            Arc::new(Bao::normal()),
+            Arc::new(Bao::kiswahili()),
+            Arc::new(Omweso::default()),
+            Arc::new(Omweso {
+                reverse_capture: false,
+                ..Omweso::default()
+            }),
+            Arc::new(Hus::default()),
+            Arc::new(Oware::default()),
+            Arc::new(Kalah::default()),
+        ]
+    }
 
-                    hand += match self.mode {
-                        Mode::Easy => {
-                            let steal = opponent.board_half[opponent_index];
-                            total_steal += steal;
-                            opponent.board_half[opponent_index] = 0;
-                            steal
-                        }
-                        Mode::Normal => {
-                            let opponent_2nd_index = 15 - opponent_index;
-                            let steal = opponent.board_half[opponent_index]
-                                + opponent.board_half[opponent_2nd_index];
-                            total_steal += steal;
-                            opponent.board_half[opponent_index] = 0;
-                            opponent.board_half[opponent_2nd_index] = 0;
-                            steal
+    /// Plays `games` seeded random games in each direction under every ruleset and calls
+    /// `check` with the game and the move before every move.
+    pub(super) fn random_moves(games: usize, mut check: impl FnMut(&Game, Move)) {
+        let mut rng = StdRng::seed_from_u64(0x6261_6f5f);
+
+        for rules in all_rules() {
+            for direction in Direction::ALL {
+                for _ in 0..games {
+                    let mut game = Game::with_rules(
+                        direction,
+                        rules.clone(),
+                        Player::new("Player 1", 0),
+                        Player::new("Player 2", 1),
+                    );
+
+                    while game.turn_count() <= rules.limits().turn_limit {
+                        let mv = match game.legal_moves().choose(&mut rng) {
+                            Some(&mv) => mv,
+                            None => break,
+                        };
+                        check(&game, mv);
+
+                        if game.make_move(mv).unwrap().result != MoveResult::Ongoing {
+                            break;
                         }
-                    };
-
-                    // check win condition after steal!
-                    if opponent.has_lost(self.mode) {
-                        return MoveResult::Won(total_steal);
                     }
                 }
             }
         }
+    }
+
+    /// Plays `actions` in order, and panics when asked for more.
+    struct Scripted(Vec<Action>);
+
+    impl Agent for Scripted {
+        fn pick_move(&mut self, _game: &Game) -> Move {
+            unreachable!("picks through act")
+        }
 
-        // check lose condition after move!
-        if player.has_lost(self.mode) {
-            MoveResult::Lost(total_steal)
-        } else {
-            MoveResult::None(total_steal)
+        fn act(&mut self, _game: &Game) -> Action {
+            assert!(!self.0.is_empty(), "asked for more actions than scripted");
+            self.0.remove(0)
         }
     }
 
-    fn steal_dry_run(
-        mut index: usize,
-        direction: Direction,
-        mode: Mode,
-        mut player: &mut Player,
-        mut opponent: &mut Player,
-    ) -> MoveResult {
-        let mut total_steal = 0;
-
-        let mut hand = player.board_half[index];
-        player.board_half[index] = 0;
-
-        while hand > 0 {
-            index = direction.next_index(index);
-            hand -= 1;
-            player.board_half[index] += 1;
-
-            if hand == 0 && player.board_half[index] >= 2 {
-                hand = player.board_half[index];
-                player.board_half[index] = 0;
-
-                // steal from opponent
-                if (8..=15).contains(&index) {
-                    let opponent_index = (15 - index) + 8;
-
-                    hand += match mode {
-                        Mode::Easy => {
-                            let steal = opponent.board_half[opponent_index];
-                            total_steal += steal;
-                            opponent.board_half[opponent_index] = 0;
-                            steal
-                        }
-                        Mode::Normal => {
-                            let opponent_2nd_index = 15 - opponent_index;
-                            let steal = opponent.board_half[opponent_index]
-                                + opponent.board_half[opponent_2nd_index];
-                            total_steal += steal;
-                            opponent.board_half[opponent_index] = 0;
-                            opponent.board_half[opponent_2nd_index] = 0;
-                            steal
-                        }
-                    };
+    /// A move that can't be played in a game sown CW.
+    const ILLEGAL: Action = Action::Move(Move {
+        index: 0,
+        direction: Direction::CCW,
+    });
+
+    fn game() -> Game {
+        Game::new(
+            Direction::CW,
+            Bao::easy(),
+            Player::new("Player 1", 0),
+            Player::new("Player 2", 1),
+        )
+    }
 
-                    // check win condition after steal!
-                    if opponent.has_lost(mode) {
-                        // always favour the winning move over the max steal move
-                        return MoveResult::None(99);
-                    }
-                }
+    #[test]
+    fn illegal_move_forfeits_the_game() {
+        let result = game()
+            .play(&mut Scripted(vec![ILLEGAL]), &mut Scripted(Vec::new()))
+            .unwrap();
+
+        assert_eq!(result.winner().map(Player::tag), Some(1));
+        assert_eq!(result.reason, Reason::Forfeit);
+        let illegal_move = result.illegal_move.expect("no illegal move");
+        assert_eq!(illegal_move.seat, Player1);
+        assert_eq!(Action::Move(illegal_move.mv), ILLEGAL);
+    }
+
+    #[test]
+    fn illegal_move_is_asked_again_as_often_as_allowed() {
+        let mut agent = Scripted(vec![ILLEGAL; 3]);
+
+        let result = game()
+            .illegal_move_policy(IllegalMovePolicy::AskAgain { attempts: 2 })
+            .play(&mut agent, &mut Scripted(Vec::new()))
+            .unwrap();
+
+        // the first pick and two attempts
+        assert!(agent.0.is_empty());
+        assert_eq!(result.reason, Reason::Forfeit);
+        assert!(result.illegal_move.is_some());
+    }
+
+    #[test]
+    fn illegal_move_aborts_the_game() {
+        let result = game()
+            .illegal_move_policy(IllegalMovePolicy::Abort)
+            .play(&mut Scripted(vec![ILLEGAL]), &mut Scripted(Vec::new()));
+
+        assert!(matches!(result, Err(MoveError::Illegal(_))));
+    }
+
+    #[test]
+    fn resigning_loses_the_game() {
+        let result = game()
+            .play(
+                &mut Scripted(vec![Action::Resign]),
+                &mut Scripted(Vec::new()),
+            )
+            .unwrap();
+
+        assert_eq!(result.winner().map(Player::tag), Some(1));
+        assert_eq!(result.reason, Reason::Resignation);
+        assert_eq!(result.turn_count, 0);
+    }
+
+    #[test]
+    fn running_out_of_time_loses_the_game() {
+        /// Takes a while to pick the first legal move.
+        struct Slow;
+
+        impl Agent for Slow {
+            fn pick_move(&mut self, game: &Game) -> Move {
+                std::thread::sleep(Duration::from_millis(1));
+                game.legal_moves()[0]
             }
         }
-        MoveResult::None(total_steal)
+
+        let result = game()
+            .time_control(TimeControl::PerMove {
+                per_move: Duration::ZERO,
+            })
+            .play(&mut Slow, &mut Slow)
+            .unwrap();
+
+        assert_eq!(result.winner().map(Player::tag), Some(1));
+        assert_eq!(result.reason, Reason::Timeout);
+        assert_eq!(result.turn_count, 0);
+    }
+
+    #[test]
+    fn game_without_legal_move_ends_before_asking_the_agent() {
+        // player 1 cannot feed the opponent and both sides keep one stone
+        let game = Setup::new(Direction::CCW, Oware::default())
+            .board(Player1, &[1, 0, 0, 0, 0, 0])
+            .board(Player2, &[0; 6])
+            .captured(Player2, 1)
+            .build(Player::new("Player 1", 0), Player::new("Player 2", 1))
+            .unwrap();
+
+        let result = game
+            .play(&mut Scripted(Vec::new()), &mut Scripted(Vec::new()))
+            .unwrap();
+
+        assert!(result.winner().is_none());
+        assert_eq!(result.reason, Reason::Rules);
+        assert_eq!(result.turn_count, 0);
+    }
+
+    /// Builds `setup` with `player1` against `player2`, panics if the position is invalid.
+    pub(super) fn set_up(setup: Setup, player1: &[u8], player2: &[u8]) -> Game {
+        setup
+            .board(Player1, player1)
+            .board(Player2, player2)
+            .build(Player::new("Player 1", 0), Player::new("Player 2", 1))
+            .expect("invalid position")
+    }
+
+    #[test]
+    fn unmake_move_restores_the_game() {
+        random_moves(20, |game, mv| {
+            let mut made = game.clone();
+            made.make_move(mv).unwrap();
+
+            assert_eq!(made.unmake_move(), Some(mv));
+            assert_eq!(made.position(), game.position());
+            assert_eq!(made.turn_count(), game.turn_count());
+            assert_eq!(made.key(), game.key());
+            assert!(made.moves().eq(game.moves()));
+        });
     }
 }
@@ -0,0 +1,144 @@
+use super::relay;
+use super::zobrist::RulesKey;
+use super::{
+    Direction, Geometry, Limits, Move, MoveKind, MoveOutcome, Position, RuleSet, Side, SowEvent,
+    Turn,
+};
+
+/// The stones of a bowl of the outer row at the start.
+const START_STONES: u8 = 4;
+
+/// The rules of Omweso, the Ugandan game on four rows of eight.
+///
+/// Moves are sown CCW from a bowl with at least two stones. A lap ending in an occupied
+/// bowl goes on from there. If that bowl is in the inner row and both of the opponent's
+/// bowls in its column hold stones, they are captured instead and sown from the bowl the
+/// move started from. A player who can't move has lost.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Omweso {
+    /// A move from one of the two leftmost bowls of either row may be sown CW if its
+    /// first lap ends in a capture. The whole move is then sown CW.
+    pub reverse_capture: bool,
+    pub limits: Limits,
+}
+
+impl Default for Omweso {
+    fn default() -> Self {
+        Self {
+            reverse_capture: true,
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl Omweso {
+    /// Whether a lap ending in `index` with `stones` in it captures.
+    #[inline(always)]
+    fn captures(&self, opponent: &Side, index: usize, stones: u8) -> bool {
+        let geometry = self.geometry();
+
+        stones >= 2 && geometry.is_inner(index) && {
+            let opponent_index = geometry.opposite(index);
+            std::iter::once(opponent_index)
+                .chain(geometry.behind(opponent_index))
+                .all(|index| opponent.board_half[index] > 0)
+        }
+    }
+
+    /// The two leftmost bowls of both rows, as seen by their player.
+    #[inline(always)]
+    fn is_reverse_bowl(&self, index: usize) -> bool {
+        let geometry = self.geometry();
+        let inner_row = geometry.inner_row();
+
+        index <= 1 || index >= inner_row.end - 2
+    }
+}
+
+impl RuleSet for Omweso {
+    fn geometry(&self) -> Geometry {
+        Geometry::default()
+    }
+
+    /// Four stones in each bowl of the outer row, a common opening for the 32 stones each
+    /// player is free to arrange. Other openings can be set up with `Setup`.
+    fn setup(&self) -> Position {
+        let geometry = self.geometry();
+
+        let mut side = Side::new(geometry, 0);
+        for index in 0..geometry.inner_row().start {
+            side.board_half[index] = START_STONES;
+        }
+
+        Position::new([side, side])
+    }
+
+    /// Moves are sown CCW whatever direction the game was created with, so both
+    /// directions are offered and `is_legal_move` turns down the CW ones.
+    fn direction_choice(&self) -> bool {
+        true
+    }
+
+    fn is_legal_move(&self, position: &Position, _directions: &[Direction], mv: Move) -> bool {
+        let player = position.current();
+
+        if player.board_half[mv.index] < 2 {
+            return false;
+        }
+
+        match mv.direction {
+            Direction::CCW => true,
+            Direction::CW => {
+                self.reverse_capture
+                    && self.is_reverse_bowl(mv.index)
+                    && self.move_kind(position, mv) == MoveKind::Mtaji
+            }
+        }
+    }
+
+    fn illegal_reason(&self, position: &Position, _directions: &[Direction], mv: Move) -> String {
+        if position.current().board_half[mv.index] < 2 {
+            String::from("Bowl must contain at least 2 stones.")
+        } else if self.reverse_capture {
+            String::from("Only a capture from the two leftmost bowls of a row may be sown CW.")
+        } else {
+            String::from("Moves are sown CCW.")
+        }
+    }
+
+    fn move_kind(&self, position: &Position, mv: Move) -> MoveKind {
+        relay::move_kind(position, mv, |opponent, index, stones| {
+            self.captures(opponent, index, stones)
+        })
+    }
+
+    fn sow(
+        &self,
+        position: &mut Position,
+        mv: Move,
+        trace: &mut Option<&mut Vec<SowEvent>>,
+    ) -> MoveOutcome {
+        relay::sow(
+            position,
+            mv,
+            self.limits.lap_limit,
+            |opponent, index, stones| self.captures(opponent, index, stones),
+            trace,
+        )
+    }
+
+    fn has_lost(&self, position: &Position, _directions: &[Direction], player: Turn) -> bool {
+        relay::has_lost(position.side(player))
+    }
+
+    fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    fn key(&self) -> u64 {
+        RulesKey::new("omweso")
+            .with(self.reverse_capture as u64)
+            .with_limits(self.limits)
+            .finish()
+    }
+}
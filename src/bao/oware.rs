@@ -0,0 +1,309 @@
+use super::trace::{record, SowEvent};
+use super::zobrist::RulesKey;
+use super::{
+    Direction, Geometry, Limits, Move, MoveKind, MoveOutcome, MoveResult, Position, RuleSet, Side,
+    TieBreak, Turn,
+};
+
+/// The stones of every bowl at the start.
+const START_STONES: u8 = 4;
+
+/// The rules of Oware abapa, on two rows of six.
+///
+/// A move sows all stones of a bowl, one by one around the whole board in the direction
+/// the game was created with, skipping the emptied bowl. If the last stone makes two or
+/// three in a bowl of the opponent, that bowl is captured into the store, and so is every
+/// bowl of the opponent before it that holds two or three, up to the first that doesn't.
+/// A capture that would take all of the opponent's stones, a grand slam, takes none.
+///
+/// A player whose opponent has no stones has to give them some. If the player to move
+/// can't, each player captures the stones on their side. Whoever captures more than half
+/// of the stones wins.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Oware {
+    /// Counting the stones left on the board to their owners is how an endless game is
+    /// settled over the board.
+    pub limits: Limits,
+}
+
+impl Default for Oware {
+    fn default() -> Self {
+        Self {
+            limits: Limits {
+                tie_break: TieBreak::Stones,
+                ..Limits::default()
+            },
+        }
+    }
+}
+
+impl Oware {
+    /// The bowl after `index` on the ring around both rows, and whether it is on the same
+    /// side as `index`.
+    #[inline(always)]
+    fn next(&self, index: usize, direction: Direction) -> (usize, bool) {
+        let last = self.geometry().bowls() - 1;
+
+        match direction {
+            Direction::CCW if index == last => (0, false),
+            Direction::CCW => (index + 1, true),
+            Direction::CW if index == 0 => (last, false),
+            Direction::CW => (index - 1, true),
+        }
+    }
+
+    /// Whether the bowl at `index` may be sown: it holds stones and, if the opponent has
+    /// none, the move gives them some.
+    #[inline(always)]
+    fn can_sow(&self, player: &Side, opponent: &Side, index: usize, direction: Direction) -> bool {
+        let stones = player.board_half[index] as usize;
+        let own_bowls = match direction {
+            Direction::CCW => self.geometry().bowls() - 1 - index,
+            Direction::CW => index,
+        };
+
+        stones > 0 && (opponent.board().iter().any(|&bowl| bowl > 0) || stones > own_bowls)
+    }
+
+    #[inline(always)]
+    fn can_move(&self, player: &Side, opponent: &Side, direction: Direction) -> bool {
+        (0..self.geometry().bowls()).any(|index| self.can_sow(player, opponent, index, direction))
+    }
+}
+
+impl RuleSet for Oware {
+    fn geometry(&self) -> Geometry {
+        Geometry::new(6, 1)
+    }
+
+    /// Four stones in every bowl.
+    fn setup(&self) -> Position {
+        let side = Side::new(self.geometry(), START_STONES);
+
+        Position::new([side, side])
+    }
+
+    fn mirror_symmetric(&self) -> bool {
+        true
+    }
+
+    fn is_legal_move(&self, position: &Position, _directions: &[Direction], mv: Move) -> bool {
+        self.can_sow(
+            position.current(),
+            position.opponent(),
+            mv.index,
+            mv.direction,
+        )
+    }
+
+    fn illegal_reason(&self, position: &Position, _directions: &[Direction], mv: Move) -> String {
+        if position.current().board_half[mv.index] == 0 {
+            String::from("Bowl must contain stones.")
+        } else {
+            String::from("The opponent has no stones, you have to give them some.")
+        }
+    }
+
+    fn sow(
+        &self,
+        position: &mut Position,
+        mv: Move,
+        trace: &mut Option<&mut Vec<SowEvent>>,
+    ) -> MoveOutcome {
+        let (player, opponent) = position.sides_mut();
+
+        let mut outcome = MoveOutcome {
+            result: MoveResult::Ongoing,
+            kind: MoveKind::Takasa,
+            steal: 0,
+            laps: 1,
+            extra_turn: false,
+        };
+
+        let mut hand = player.take(mv.index);
+        record(
+            trace,
+            SowEvent::Pickup {
+                index: mv.index,
+                stones: hand,
+            },
+        );
+
+        let mut index = mv.index;
+        let mut own = true;
+        while hand > 0 {
+            let (next, same_side) = self.next(index, mv.direction);
+            index = next;
+            own = own == same_side;
+
+            // the emptied bowl is skipped when a move sows more than a full round
+            if own && index == mv.index {
+                continue;
+            }
+
+            hand -= 1;
+            if own {
+                player.add(index, 1);
+                record(trace, SowEvent::Drop { index });
+            } else {
+                opponent.add(index, 1);
+                record(trace, SowEvent::Feed { index });
+            }
+        }
+
+        if !own {
+            // the bowls to capture, back from the last one along the opponent's row
+            let mut captured = Vec::new();
+            loop {
+                if !(2..=3).contains(&opponent.board_half[index]) {
+                    break;
+                }
+                captured.push(index);
+
+                let (previous, same_side) = self.next(index, mv.direction.reverse());
+                if !same_side {
+                    break;
+                }
+                index = previous;
+            }
+
+            let steal = captured
+                .iter()
+                .map(|&index| opponent.board_half[index])
+                .sum::<u8>();
+            let grand_slam = steal == opponent.board().iter().sum::<u8>();
+
+            if steal > 0 && !grand_slam {
+                for index in captured {
+                    let stones = opponent.take(index);
+                    record(trace, SowEvent::Capture { index, stones });
+                }
+                player.capture(steal);
+                outcome.kind = MoveKind::Mtaji;
+                outcome.steal = steal;
+            }
+        }
+
+        if player.has_majority(opponent) {
+            outcome.result = MoveResult::Won;
+        } else if !self.can_move(opponent, player, mv.direction) {
+            // the stones left on the board go to their owners
+            player.capture_board();
+            opponent.capture_board();
+
+            outcome.result = match player.captured.cmp(&opponent.captured) {
+                std::cmp::Ordering::Greater => MoveResult::Won,
+                std::cmp::Ordering::Less => MoveResult::Lost,
+                std::cmp::Ordering::Equal => MoveResult::Drawn,
+            };
+        }
+
+        outcome
+    }
+
+    fn has_stores(&self) -> bool {
+        true
+    }
+
+    /// A player has lost once the opponent captured more than half of the stones, or once
+    /// the player to move cannot move and the stones left on the board leave them behind.
+    fn has_lost(&self, position: &Position, directions: &[Direction], player: Turn) -> bool {
+        let (side, opponent) = (position.side(player), position.side(player.other()));
+        if opponent.has_majority(side) {
+            return true;
+        }
+
+        let stuck = !directions
+            .iter()
+            .any(|&direction| self.can_move(position.current(), position.opponent(), direction));
+
+        stuck && side.stones() < opponent.stones()
+    }
+
+    fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    fn key(&self) -> u64 {
+        RulesKey::new("oware").with_limits(self.limits).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bao::tests::set_up;
+    use crate::bao::{Game, Player, Setup, SetupError, Turn::*};
+
+    /// A game sown CCW in which player 1 is to move with `player1` against `player2`.
+    fn game(player1: &[u8], player2: &[u8]) -> Game {
+        set_up(
+            Setup::new(Direction::CCW, Oware::default()),
+            player1,
+            player2,
+        )
+    }
+
+    #[test]
+    fn grand_slam_captures_nothing() {
+        let mut game = game(&[1, 0, 0, 0, 0, 2], &[1, 2, 0, 0, 0, 0]);
+
+        let outcome = game.make_move(Move::new(5, Direction::CCW)).unwrap();
+
+        assert_eq!(outcome.kind, MoveKind::Takasa);
+        assert_eq!(outcome.steal, 0);
+        assert_eq!(game.position().side(Player1).captured(), 0);
+        assert_eq!(game.position().side(Player2).board(), &[2, 3, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn opponent_without_stones_has_to_be_fed() {
+        let game = game(&[3, 0, 0, 0, 1, 1], &[0; 6]);
+
+        assert_eq!(game.legal_moves(), vec![Move::new(5, Direction::CCW)]);
+    }
+
+    #[test]
+    fn capture_stops_at_the_end_of_the_opponents_row() {
+        let mut game = game(&[0, 0, 0, 0, 4, 1], &[1, 2, 1, 2, 4, 4]);
+
+        let outcome = game.make_move(Move::new(4, Direction::CCW)).unwrap();
+
+        assert_eq!(outcome.kind, MoveKind::Mtaji);
+        assert_eq!(outcome.steal, 7);
+        assert_eq!(game.position().side(Player1).captured(), 7);
+        // the own bowl before the opponent's row holds two but is not captured
+        assert_eq!(game.position().side(Player1).board(), &[0, 0, 0, 0, 0, 2]);
+        assert_eq!(game.position().side(Player2).board(), &[0, 0, 0, 2, 4, 4]);
+    }
+
+    #[test]
+    fn lap_of_twelve_skips_the_emptied_bowl() {
+        let mut game = game(&[12, 0, 0, 0, 0, 0], &[1; 6]);
+
+        let outcome = game.make_move(Move::new(0, Direction::CCW)).unwrap();
+
+        assert_eq!(outcome.steal, 0);
+        assert_eq!(game.position().side(Player1).board(), &[0, 2, 1, 1, 1, 1]);
+        assert_eq!(game.position().side(Player2).board(), &[2; 6]);
+    }
+
+    #[test]
+    fn setup_rejects_a_player_to_move_who_cannot_feed() {
+        let setup = |player1: &[u8], player2: &[u8]| {
+            Setup::new(Direction::CCW, Oware::default())
+                .board(Player1, player1)
+                .board(Player2, player2)
+                .build(Player::new("Player 1", 0), Player::new("Player 2", 1))
+                .map(|_| ())
+        };
+
+        // the stone cannot reach the opponent, who keeps nothing
+        assert_eq!(
+            setup(&[1, 0, 0, 0, 0, 0], &[0; 6]),
+            Err(SetupError::GameOver(Player2))
+        );
+        // the stones left on the board go to player 2
+        assert_eq!(setup(&[0; 6], &[4; 6]), Err(SetupError::GameOver(Player1)));
+    }
+}
@@ -0,0 +1,348 @@
+use super::invariant::add_stones;
+use super::trace::{record, SowEvent};
+use super::zobrist::KEYS;
+use super::{Direction, Geometry, Move, MoveKind, RuleSet, Turn, Turn::*, MAX_BOWLS};
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// One player's half of the board, their store and their nyumba.
+///
+/// The bowls are kept in a fixed array so that sides and positions are `Copy`, the bowls
+/// past the geometry stay empty. Bowls are changed through `set` and `add`, which keep the
+/// Zobrist key of the bowls up to date.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Side {
+    pub(super) geometry: Geometry,
+    pub(super) board_half: [u8; MAX_BOWLS],
+    /// The seat the side is at, set by `Position::new`.
+    seat: Turn,
+    /// The Zobrist key of the bowls.
+    key: u64,
+    pub(super) store: u8,
+    pub(super) captured: u8,
+    pub(super) nyumba: bool,
+}
+
+impl Side {
+    /// A side with `stones` in each bowl.
+    pub(super) fn new(geometry: Geometry, stones: u8) -> Self {
+        let mut side = Self {
+            geometry,
+            board_half: [0; MAX_BOWLS],
+            seat: Player1,
+            key: 0,
+            store: 0,
+            captured: 0,
+            nyumba: false,
+        };
+        side.board_mut().fill(stones);
+        side
+    }
+
+    /// Where the first lap of sowing the bowl at `index` in `direction` ends and how many
+    /// stones that bowl holds then. An empty bowl ends where it starts, holding none.
+    pub(super) fn lap_end(&self, index: usize, direction: Direction) -> (usize, u8) {
+        let geometry = &self.geometry;
+        let hand = self.board_half[index] as usize;
+        if hand == 0 {
+            return (index, 0);
+        }
+
+        let bowls = geometry.bowls();
+        let steps = (hand - 1) % bowls + 1;
+        let end = (0..steps).fold(index, |index, _| geometry.next_index(index, direction));
+        let before = if end == index {
+            0
+        } else {
+            self.board_half[end]
+        };
+
+        (end, before + ((hand - steps) / bowls + 1) as u8)
+    }
+
+    /// All stones of the player, on the board, in the store and captured.
+    #[inline(always)]
+    pub fn stones(&self) -> usize {
+        self.board()
+            .iter()
+            .map(|&bowl| bowl as usize)
+            .sum::<usize>()
+            + self.store as usize
+            + self.captured as usize
+    }
+
+    /// Stones not yet brought onto the board.
+    #[inline(always)]
+    pub fn store(&self) -> u8 {
+        self.store
+    }
+
+    /// Stones taken off the board by captures, in rules that keep them out of play.
+    #[inline(always)]
+    pub fn captured(&self) -> u8 {
+        self.captured
+    }
+
+    /// Whether the player captured more than half of the stones in play.
+    #[inline(always)]
+    pub(super) fn has_majority(&self, opponent: &Side) -> bool {
+        2 * self.captured as usize > self.stones() + opponent.stones()
+    }
+
+    /// Takes `stones` out of play.
+    #[inline(always)]
+    pub(super) fn capture(&mut self, stones: u8) {
+        self.captured = add_stones(self.captured, stones);
+    }
+
+    /// Captures the stones left on the board.
+    #[inline(always)]
+    pub(super) fn capture_board(&mut self) {
+        for index in 0..self.geometry.bowls() {
+            let stones = self.take(index);
+            self.capture(stones);
+        }
+    }
+
+    /// As long as there are stones in the store, every move places one of them.
+    #[inline(always)]
+    pub fn in_namua(&self) -> bool {
+        self.store > 0
+    }
+
+    #[inline(always)]
+    pub fn board(&self) -> &[u8] {
+        &self.board_half[..self.geometry.bowls()]
+    }
+
+    #[inline(always)]
+    pub(super) fn board_mut(&mut self) -> &mut [u8] {
+        &mut self.board_half[..self.geometry.bowls()]
+    }
+
+    #[inline(always)]
+    pub(super) fn inner_row(&self) -> &[u8] {
+        &self.board_half[self.geometry.inner_row()]
+    }
+
+    /// Whether the nyumba has not been emptied yet.
+    #[inline(always)]
+    pub fn has_nyumba(&self) -> bool {
+        self.nyumba
+    }
+
+    /// The nyumba only takes effect while it is owned and holds at least six stones.
+    #[inline(always)]
+    pub fn is_nyumba_functional(&self) -> bool {
+        self.nyumba && self.board_half[self.geometry.nyumba()] >= 6
+    }
+
+    /// Whether the owner's sowing must leave the stones in this bowl.
+    #[inline(always)]
+    pub(super) fn keeps(&self, index: usize) -> bool {
+        index == self.geometry.nyumba() && self.is_nyumba_functional()
+    }
+
+    /// Empties a bowl and returns its stones.
+    #[inline(always)]
+    pub(super) fn take(&mut self, index: usize) -> u8 {
+        if index == self.geometry.nyumba() {
+            self.nyumba = false;
+        }
+
+        let stones = self.board_half[index];
+        self.set(index, 0);
+        stones
+    }
+
+    /// Puts `stones` into a bowl instead of the stones it holds.
+    #[inline(always)]
+    pub(super) fn set(&mut self, index: usize, stones: u8) {
+        let keys = &KEYS.bowls[self.seat as usize][index];
+        self.key ^= keys[self.board_half[index] as usize] ^ keys[stones as usize];
+        self.board_half[index] = stones;
+    }
+
+    /// Drops `stones` into a bowl.
+    #[inline(always)]
+    pub(super) fn add(&mut self, index: usize, stones: u8) {
+        self.set(index, add_stones(self.board_half[index], stones));
+    }
+
+    /// Takes the seat and computes the key of the bowls from scratch, after the bowls
+    /// were changed directly.
+    fn rekey(&mut self, seat: Turn) {
+        self.seat = seat;
+        self.key = (0..self.geometry.bowls())
+            .map(|index| KEYS.bowls[seat as usize][index][self.board_half[index] as usize])
+            .fold(0, |key, bowl| key ^ bowl);
+    }
+
+    /// The Zobrist key of the side: its bowls, store, captured stones and nyumba.
+    #[inline(always)]
+    fn key(&self) -> u64 {
+        let seat = self.seat as usize;
+        let nyumba = if self.nyumba { KEYS.nyumbas[seat] } else { 0 };
+
+        self.key
+            ^ KEYS.stores[seat][self.store as usize]
+            ^ KEYS.captured[seat][self.captured as usize]
+            ^ nyumba
+    }
+}
+
+impl fmt::Debug for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Side")
+            .field("geometry", &self.geometry)
+            .field("board", &self.board())
+            .field("store", &self.store)
+            .field("captured", &self.captured)
+            .field("nyumba", &self.nyumba)
+            .finish()
+    }
+}
+
+/// How a move left the game, seen from the player who made it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MoveResult {
+    Ongoing,
+    Won,
+    Lost,
+    /// The move ended the game and neither player won.
+    Drawn,
+    /// The move sowed more laps than the lap limit allows, the game is settled by
+    /// `Limits::infinite_move`.
+    Infinite,
+}
+
+/// Everything a move did.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MoveOutcome {
+    pub result: MoveResult,
+    pub kind: MoveKind,
+    /// The stones stolen over all laps.
+    pub steal: u8,
+    pub laps: usize,
+    /// The player moves again instead of passing the turn.
+    pub extra_turn: bool,
+}
+
+/// The stones of both players and whose turn it is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub(super) sides: [Side; 2],
+    pub(super) turn: Turn,
+}
+
+impl Hash for Position {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.key());
+    }
+}
+
+impl Position {
+    /// A position with the sides of player 1 and player 2, player 1 to move.
+    pub(super) fn new(sides: [Side; 2]) -> Self {
+        let mut position = Self {
+            sides,
+            turn: Player1,
+        };
+        position.rekey();
+        position
+    }
+
+    /// Computes the keys of the bowls from scratch, after they were changed directly.
+    pub(super) fn rekey(&mut self) {
+        for player in [Player1, Player2] {
+            self.side_mut(player).rekey(player);
+        }
+    }
+
+    /// The Zobrist key of the position: the bowls, stores, nyumbas and the player to
+    /// move. The keys of the bowls are kept up to date by every move, the rest is added
+    /// here.
+    #[inline(always)]
+    pub fn key(&self) -> u64 {
+        let turn = if self.turn == Player2 { KEYS.turn } else { 0 };
+
+        self.sides[0].key() ^ self.sides[1].key() ^ turn
+    }
+
+    /// The player to move.
+    #[inline(always)]
+    pub fn turn(&self) -> Turn {
+        self.turn
+    }
+
+    #[inline(always)]
+    pub fn side(&self, player: Turn) -> &Side {
+        &self.sides[player as usize]
+    }
+
+    #[inline(always)]
+    pub(super) fn side_mut(&mut self, player: Turn) -> &mut Side {
+        &mut self.sides[player as usize]
+    }
+
+    /// The side of the player to move.
+    #[inline(always)]
+    pub fn current(&self) -> &Side {
+        self.side(self.turn)
+    }
+
+    /// The side of the player waiting for their turn.
+    #[inline(always)]
+    pub fn opponent(&self) -> &Side {
+        self.side(self.turn.other())
+    }
+
+    /// The sides of the player to move and of the opponent.
+    #[inline(always)]
+    pub(super) fn sides_mut(&mut self) -> (&mut Side, &mut Side) {
+        let [player1, player2] = &mut self.sides;
+        if self.turn == Player1 {
+            (player1, player2)
+        } else {
+            (player2, player1)
+        }
+    }
+
+    /// Plays `mv` for the player to move and returns the position after it, with the
+    /// next player to move, and what the move did. The move has to be valid.
+    pub fn apply<R: RuleSet + ?Sized>(&self, rules: &R, mv: Move) -> (Position, MoveOutcome) {
+        let mut position = *self;
+        let outcome = position.play(rules, mv, None);
+        (position, outcome)
+    }
+
+    /// Like `apply`, but also returns every step of the move.
+    pub fn apply_traced<R: RuleSet + ?Sized>(
+        &self,
+        rules: &R,
+        mv: Move,
+    ) -> (Position, MoveOutcome, Vec<SowEvent>) {
+        let mut position = *self;
+        let mut events = Vec::new();
+        let outcome = position.play(rules, mv, Some(&mut events));
+        (position, outcome, events)
+    }
+
+    /// Plays `mv` in place and passes the turn, unless the move earned another one.
+    pub(super) fn play<R: RuleSet + ?Sized>(
+        &mut self,
+        rules: &R,
+        mv: Move,
+        mut trace: Option<&mut Vec<SowEvent>>,
+    ) -> MoveOutcome {
+        let outcome = rules.sow(self, mv, &mut trace);
+        if outcome.result != MoveResult::Ongoing {
+            record(&mut trace, SowEvent::Decided(outcome.result));
+        }
+        if !outcome.extra_turn {
+            self.turn = self.turn.other();
+        }
+        outcome
+    }
+}
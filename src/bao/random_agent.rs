@@ -1,8 +1,6 @@
-use super::{Agent, Game, Turn::*};
+use super::{Agent, Game, Move};
 
-use std::iter;
-
-use rand::Rng;
+use rand::seq::IteratorRandom;
 
 pub struct RandomAgent;
 
@@ -13,15 +11,9 @@ impl Default for RandomAgent {
 }
 
 impl Agent for RandomAgent {
-    fn pick_index(&mut self, game: &Game) -> usize {
-        let player = if game.turn() == Player1 {
-            &game.player1
-        } else {
-            &game.player2
-        };
-
-        iter::repeat_with(|| rand::thread_rng().gen_range(0..16))
-            .find(|&index| player.is_valid_index(index))
-            .expect("No valid index?")
+    fn pick_move(&mut self, game: &Game) -> Move {
+        game.legal_moves_iter()
+            .choose(&mut rand::thread_rng())
+            .expect("No valid move?")
     }
 }
@@ -0,0 +1,122 @@
+use super::invariant::add_stones;
+use super::trace::{record, SowEvent};
+use super::{Move, MoveKind, MoveOutcome, MoveResult, Position, Side};
+
+/// Whether `mv` captures at the end of its first lap, with `captures` telling whether a
+/// lap ending in a bowl with that many stones captures from the opponent.
+pub(super) fn move_kind(
+    position: &Position,
+    mv: Move,
+    captures: impl Fn(&Side, usize, u8) -> bool,
+) -> MoveKind {
+    let (index, stones) = position.current().lap_end(mv.index, mv.direction);
+
+    if captures(position.opponent(), index, stones) {
+        MoveKind::Mtaji
+    } else {
+        MoveKind::Takasa
+    }
+}
+
+/// Relay sowing of the four-row games, Omweso and Hus.
+///
+/// A lap ending in a bowl that held stones goes on from there. If `captures` tells that
+/// the lap ending in a bowl with that many stones captures, the opponent's bowl opposite
+/// and the one behind it are emptied instead and their stones sown from the bowl the move
+/// started from. A player left without a bowl of two stones has lost.
+pub(super) fn sow(
+    position: &mut Position,
+    mv: Move,
+    lap_limit: usize,
+    captures: impl Fn(&Side, usize, u8) -> bool,
+    trace: &mut Option<&mut Vec<SowEvent>>,
+) -> MoveOutcome {
+    let kind = move_kind(position, mv, &captures);
+    let (player, opponent) = position.sides_mut();
+    let geometry = player.geometry;
+
+    let Move {
+        mut index,
+        direction,
+    } = mv;
+
+    let mut outcome = MoveOutcome {
+        result: MoveResult::Ongoing,
+        kind,
+        steal: 0,
+        laps: 1,
+        extra_turn: false,
+    };
+
+    let mut hand = player.take(index);
+    record(
+        trace,
+        SowEvent::Pickup {
+            index,
+            stones: hand,
+        },
+    );
+
+    loop {
+        if hand == 0 {
+            if player.board_half[index] < 2 {
+                break;
+            }
+
+            if captures(opponent, index, player.board_half[index]) {
+                let opponent_index = geometry.opposite(index);
+                let steal = std::iter::once(opponent_index)
+                    .chain(geometry.behind(opponent_index))
+                    .map(|index| {
+                        let stones = opponent.take(index);
+                        record(trace, SowEvent::Capture { index, stones });
+                        stones
+                    })
+                    .sum::<u8>();
+                outcome.steal = add_stones(outcome.steal, steal);
+
+                if has_lost(opponent) {
+                    outcome.result = MoveResult::Won;
+                    return outcome;
+                }
+
+                // the stolen stones are sown from the bowl the move started from
+                index = geometry.next_index(mv.index, direction.reverse());
+                hand = steal;
+            } else {
+                hand = player.take(index);
+                record(
+                    trace,
+                    SowEvent::Pickup {
+                        index,
+                        stones: hand,
+                    },
+                );
+            }
+
+            // the stones left in hand are not put back, an infinite move ends the game
+            outcome.laps += 1;
+            if outcome.laps > lap_limit {
+                outcome.result = MoveResult::Infinite;
+                return outcome;
+            }
+        }
+
+        index = geometry.next_index(index, direction);
+        hand -= 1;
+        player.add(index, 1);
+        record(trace, SowEvent::Drop { index });
+    }
+
+    if has_lost(player) {
+        outcome.result = MoveResult::Lost;
+    }
+
+    outcome
+}
+
+/// A player without a bowl of two stones can't move.
+#[inline(always)]
+pub(super) fn has_lost(side: &Side) -> bool {
+    side.board().iter().all(|&bowl| bowl < 2)
+}
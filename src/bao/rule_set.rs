@@ -0,0 +1,90 @@
+use super::{
+    Direction, Geometry, Limits, Move, MoveKind, MoveOutcome, Position, SetupError, SowEvent, Turn,
+};
+
+use std::fmt;
+
+/// The rules of a mancala game: how it starts, which moves are legal, how a move is sown
+/// and captures, and when a player has lost. `Game` only runs the turns.
+pub trait RuleSet: fmt::Debug + Send + Sync {
+    fn geometry(&self) -> Geometry;
+
+    /// The position the game starts in, player 1 to move.
+    fn setup(&self) -> Position;
+
+    /// Each move picks its own sowing direction. Otherwise every move is sown in the
+    /// direction the game was created with.
+    fn direction_choice(&self) -> bool {
+        false
+    }
+
+    /// Mirroring a position left to right and sowing every move the other way leads to
+    /// the same game. Nothing but the direction may tell left from right.
+    fn mirror_symmetric(&self) -> bool {
+        false
+    }
+
+    /// Whether the player to move may play `mv`. The move is on the board and sown in one
+    /// of `directions`, the directions the player may sow in.
+    fn is_legal_move(&self, position: &Position, directions: &[Direction], mv: Move) -> bool;
+
+    /// Why the player to move may not play the illegal `mv`, to tell a human player.
+    fn illegal_reason(&self, _position: &Position, _directions: &[Direction], _mv: Move) -> String {
+        String::from("The rules don't allow this move.")
+    }
+
+    /// Whether `mv` captures. Only meaningful for a legal move.
+    fn move_kind(&self, position: &Position, mv: Move) -> MoveKind {
+        let (_, outcome) = position.apply(self, mv);
+
+        if outcome.steal > 0 {
+            MoveKind::Mtaji
+        } else {
+            MoveKind::Takasa
+        }
+    }
+
+    /// Sows the legal move `mv` for the player to move, with all its captures, and tells
+    /// whether it decided the game and whether the player moves again. Passing the turn
+    /// is left to the caller.
+    fn sow(
+        &self,
+        position: &mut Position,
+        mv: Move,
+        trace: &mut Option<&mut Vec<SowEvent>>,
+    ) -> MoveOutcome;
+
+    /// Captured stones are kept in the players' stores instead of going back into play.
+    fn has_stores(&self) -> bool {
+        false
+    }
+
+    /// The game starts with a namua phase, in which the stones held in the stores are
+    /// brought onto the board one per turn.
+    fn has_namua(&self) -> bool {
+        false
+    }
+
+    /// Whether `player` has lost in `position`, with the player to move sowing in one of
+    /// `directions`.
+    fn has_lost(&self, position: &Position, directions: &[Direction], player: Turn) -> bool;
+
+    /// Checks that the rules can set up their starting position, before `setup` is called.
+    fn validate_rules(&self) -> Result<(), SetupError> {
+        Ok(())
+    }
+
+    /// Checks the parts of a set up position only these rules know about.
+    fn validate(&self, _position: &Position) -> Result<(), SetupError> {
+        Ok(())
+    }
+
+    /// How a game that doesn't end by itself is settled.
+    fn limits(&self) -> Limits {
+        Limits::default()
+    }
+
+    /// The key of the rules in the Zobrist keys of games. Rules that play differently
+    /// need different keys, and a key has to stay the same across versions to be stored.
+    fn key(&self) -> u64;
+}
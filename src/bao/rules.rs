@@ -0,0 +1,696 @@
+use super::invariant::add_stones;
+use super::trace::{record, SowEvent};
+use super::zobrist::RulesKey;
+use super::{
+    Direction, Geometry, Limits, Move, MoveKind, MoveOutcome, MoveResult, Position, RuleSet,
+    SetupError, Side, Turn, Turn::*,
+};
+
+use std::iter;
+
+/// Stones taken from a taxed nyumba.
+const NYUMBA_TAX: u8 = 2;
+
+/// The kichwa to sow stolen stones from and the direction to sow them in. The kichwas are
+/// the end bowls of the inner row. A steal in a kimbi, the two bowls at either end of the
+/// inner row, is sown from the kichwa at that end towards the middle. Any other steal
+/// keeps the direction of the sowing.
+#[inline(always)]
+fn kichwa(geometry: &Geometry, index: usize, direction: Direction) -> (usize, Direction) {
+    let inner_row = geometry.inner_row();
+
+    let direction = if index <= inner_row.start + 1 {
+        Direction::CCW
+    } else if index >= inner_row.end - 2 {
+        Direction::CW
+    } else {
+        direction
+    };
+
+    (geometry.kichwa(direction), direction)
+}
+
+/// The rules of Bao. `Bao::easy` and `Bao::normal` are the simplified variants the crate
+/// started with; the other rules of Bao la Kiswahili can be switched on one by one, or all
+/// at once with `Bao::kiswahili`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Bao {
+    pub geometry: Geometry,
+    /// A steal also takes the bowl behind the opposite bowl.
+    pub capture_behind: bool,
+    /// A player also loses once their inner row is empty. Otherwise only once no bowl
+    /// holds two stones.
+    pub inner_row_loses: bool,
+    /// Each move picks its own sowing direction. Otherwise every move is sown in the
+    /// direction the game was created with.
+    pub direction_choice: bool,
+    /// Start with the namua phase: most stones are held in the players' stores and
+    /// brought onto the board one per turn.
+    pub namua: bool,
+    /// Each player owns a nyumba. While it is functional it is never emptied by its
+    /// owner's sowing: a sowing ending in it stops, a steal from it only sows the stolen
+    /// stones and a non-stealing namua move on it only takes two stones (the nyumba is
+    /// taxed). It is lost for good once emptied, by a move from it or by a steal.
+    pub nyumba: bool,
+    /// Only steal when the opponent's opposite bowl holds stones, and sow the stolen
+    /// stones from a kichwa. The stealing bowl keeps its own stones. Otherwise the stolen
+    /// stones are added to the stealing bowl's stones and sown on from there.
+    pub kichwa: bool,
+    /// A player has to steal if any of their moves steals (mtaji). Only otherwise a move
+    /// that does not steal (takasa) may be played: from the inner row if any of its bowls
+    /// can be sown, and on a functional nyumba only if there is no other bowl to place a
+    /// stone in. A takasa never steals, not even on later laps.
+    pub compulsory_capture: bool,
+    /// Every relay and every steal starts a new lap.
+    pub limits: Limits,
+}
+
+impl Bao {
+    /// A player loses once their inner row is empty, a steal only takes the opposite bowl.
+    pub fn easy() -> Self {
+        Self {
+            geometry: Geometry::default(),
+            capture_behind: false,
+            inner_row_loses: true,
+            direction_choice: false,
+            namua: false,
+            nyumba: false,
+            kichwa: false,
+            compulsory_capture: false,
+            limits: Limits::default(),
+        }
+    }
+
+    /// A player loses once no bowl can be sown, a steal takes both bowls of a column.
+    pub fn normal() -> Self {
+        Self {
+            capture_behind: true,
+            inner_row_loses: false,
+            ..Self::easy()
+        }
+    }
+
+    /// Bao la Kiswahili: normal Bao with the namua phase, the nyumba, kichwas, compulsory
+    /// capture and a sowing direction picked by every move.
+    pub fn kiswahili() -> Self {
+        Self {
+            direction_choice: true,
+            namua: true,
+            nyumba: true,
+            kichwa: true,
+            compulsory_capture: true,
+            ..Self::normal()
+        }
+    }
+
+    /// The opening layout of Bao la Kiswahili: six stones in the nyumba, two in each of
+    /// the two bowls to its right and the remaining stones in the store. On a board of
+    /// 2x8 bowls that leaves 22 stones in the store.
+    fn setup_namua(&self, side: &mut Side) {
+        let nyumba = self.geometry.nyumba();
+
+        let stones = side.board().len() * 2;
+        side.board_mut().fill(0);
+        side.board_half[nyumba] = 6;
+        side.board_half[nyumba - 1] = 2;
+        side.board_half[nyumba - 2] = 2;
+        side.store = (stones - 10) as u8;
+    }
+
+    /// Whether the bowl can be sown, ignoring compulsory capture.
+    #[inline(always)]
+    fn can_sow(&self, side: &Side, index: usize) -> bool {
+        if side.in_namua() {
+            self.geometry.is_inner(index) && side.board_half[index] > 0
+        } else {
+            side.board_half[index] > 1
+        }
+    }
+
+    /// The bowls captured by a steal on `index`, which is opposite to the stealing bowl.
+    #[inline(always)]
+    fn captured(&self, index: usize) -> impl Iterator<Item = usize> {
+        let behind = if self.capture_behind {
+            self.geometry.behind(index)
+        } else {
+            None
+        };
+
+        iter::once(index).chain(behind)
+    }
+
+    /// The stones a steal on `index` would take.
+    #[inline(always)]
+    fn stealable(&self, side: &Side, index: usize) -> u8 {
+        self.captured(index)
+            .map(|index| side.board_half[index])
+            .sum()
+    }
+
+    /// Empties the bowls captured by a steal on `index` and returns the stolen stones.
+    #[inline(always)]
+    fn give_up(&self, side: &mut Side, index: usize, trace: &mut Option<&mut Vec<SowEvent>>) -> u8 {
+        self.captured(index)
+            .map(|index| {
+                let stones = side.take(index);
+                record(trace, SowEvent::Capture { index, stones });
+                stones
+            })
+            .sum()
+    }
+
+    /// Whether any move the player to move can sow steals.
+    fn has_mtaji_move(&self, position: &Position, directions: &[Direction]) -> bool {
+        (0..self.geometry.bowls())
+            .filter(|&index| self.can_sow(position.current(), index))
+            .flat_map(|index| {
+                directions
+                    .iter()
+                    .map(move |&direction| Move::new(index, direction))
+            })
+            .any(|mv| self.move_kind(position, mv) == MoveKind::Mtaji)
+    }
+
+    #[inline(always)]
+    fn side_has_lost(&self, side: &Side) -> bool {
+        // If the inner row is empty during namua there is nowhere to place a stone: lost
+        if side.in_namua() {
+            return side.inner_row().iter().all(|&bowl| bowl == 0);
+        }
+
+        // If there is at most one stone per bowl: lost
+        if side.board().iter().all(|&bowl| bowl < 2) {
+            return true;
+        }
+
+        // If the inner row loses and is empty: lost
+        if self.inner_row_loses && side.inner_row().iter().all(|&bowl| bowl == 0) {
+            return true;
+        }
+
+        // Otherwise: not lost
+        false
+    }
+}
+
+impl RuleSet for Bao {
+    fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    /// Two stones per bowl, or the namua layout. Panics unless the rules pass
+    /// `validate_rules`.
+    fn setup(&self) -> Position {
+        if let Err(error) = self.validate_rules() {
+            panic!("{}", error);
+        }
+
+        let mut side = Side::new(self.geometry, 2);
+        side.nyumba = self.nyumba;
+        if self.namua {
+            self.setup_namua(&mut side);
+        }
+
+        Position::new([side, side])
+    }
+
+    fn direction_choice(&self) -> bool {
+        self.direction_choice
+    }
+
+    /// The nyumba is right of the middle of the inner row, it breaks the symmetry.
+    fn mirror_symmetric(&self) -> bool {
+        !self.nyumba
+    }
+
+    fn is_legal_move(&self, position: &Position, directions: &[Direction], mv: Move) -> bool {
+        let player = position.current();
+
+        if !self.can_sow(player, mv.index) {
+            return false;
+        }
+
+        if !self.compulsory_capture || self.move_kind(position, mv) == MoveKind::Mtaji {
+            return true;
+        }
+
+        if self.has_mtaji_move(position, directions) {
+            return false;
+        }
+
+        let geometry = &self.geometry;
+        let mut other_inner_bowls = geometry
+            .inner_row()
+            .filter(|&index| index != mv.index)
+            .map(|index| player.board_half[index]);
+        if player.in_namua() {
+            // the nyumba is only taxed if there is no other bowl to place the stone in
+            !player.keeps(mv.index) || other_inner_bowls.all(|bowl| bowl == 0)
+        } else {
+            // the outer row is only sown if the inner row can't be
+            geometry.is_inner(mv.index) || other_inner_bowls.all(|bowl| bowl < 2)
+        }
+    }
+
+    fn illegal_reason(&self, position: &Position, directions: &[Direction], mv: Move) -> String {
+        let side = position.current();
+        let stones = side.board_half[mv.index];
+
+        if side.in_namua() && !(self.geometry.is_inner(mv.index) && stones > 0) {
+            let inner_row = self.geometry.inner_row();
+            format!(
+                "Bowl must be in the inner row ({} to {}) and contain stones.",
+                inner_row.start,
+                inner_row.end - 1
+            )
+        } else if !side.in_namua() && stones < 2 {
+            String::from("Bowl must contain at least 2 stones.")
+        } else if self.has_mtaji_move(position, directions) {
+            String::from("You have to steal if you can.")
+        } else {
+            String::from(
+                "Sow the inner row if you can, and only tax the nyumba if there is no other bowl.",
+            )
+        }
+    }
+
+    /// Whether `mv` steals in its first lap.
+    fn move_kind(&self, position: &Position, mv: Move) -> MoveKind {
+        let geometry = &self.geometry;
+        let player = position.current();
+        let opponent = position.opponent();
+        let placing = player.in_namua();
+
+        // where the first lap ends and how many stones the bowl holds then
+        let (index, stones) = if placing {
+            (mv.index, player.board_half[mv.index] + 1)
+        } else {
+            player.lap_end(mv.index, mv.direction)
+        };
+
+        let opponent_index = geometry.opposite(index);
+        let steals = stones >= 2
+            && geometry.is_inner(index)
+            && if self.kichwa || placing {
+                opponent.board_half[opponent_index] > 0
+            } else {
+                self.stealable(opponent, opponent_index) > 0
+            };
+
+        if steals {
+            MoveKind::Mtaji
+        } else {
+            MoveKind::Takasa
+        }
+    }
+
+    fn sow(
+        &self,
+        position: &mut Position,
+        mv: Move,
+        trace: &mut Option<&mut Vec<SowEvent>>,
+    ) -> MoveOutcome {
+        let geometry = &self.geometry;
+
+        let kind = self.move_kind(position, mv);
+        let takasa = self.compulsory_capture && kind == MoveKind::Takasa;
+
+        let (player, opponent) = position.sides_mut();
+
+        let Move {
+            mut index,
+            mut direction,
+        } = mv;
+
+        let mut outcome = MoveOutcome {
+            result: MoveResult::Ongoing,
+            kind,
+            steal: 0,
+            laps: 1,
+            extra_turn: false,
+        };
+
+        // During namua the stone from the store ends a lap in the chosen bowl right away.
+        let mut placing = player.in_namua();
+        let mut hand = if placing {
+            player.store -= 1;
+            player.add(index, 1);
+            record(trace, SowEvent::Place { index });
+            0
+        } else {
+            let stones = player.take(index);
+            record(trace, SowEvent::Pickup { index, stones });
+            stones
+        };
+
+        loop {
+            if hand == 0 {
+                if player.board_half[index] < 2 {
+                    break;
+                }
+
+                // Every lap ending in the inner row steals, even if there is nothing to steal.
+                // A kichwa steal and the stone from the store need stones opposite though.
+                let opponent_index = geometry.opposite(index);
+                let steals = !takasa
+                    && geometry.is_inner(index)
+                    && (opponent.board_half[opponent_index] > 0 || !(self.kichwa || placing));
+
+                // steal from opponent
+                if steals {
+                    let steal = self.give_up(opponent, opponent_index, trace);
+                    outcome.steal = add_stones(outcome.steal, steal);
+
+                    if self.kichwa {
+                        let (kichwa, kichwa_direction) = kichwa(geometry, index, direction);
+                        direction = kichwa_direction;
+                        index = geometry.next_index(kichwa, direction.reverse());
+                        hand = steal;
+                    } else if player.keeps(index) {
+                        hand = steal;
+                    } else {
+                        let stones = player.take(index);
+                        record(trace, SowEvent::Pickup { index, stones });
+                        hand = add_stones(stones, steal);
+                    }
+
+                    // check win condition after steal!
+                    if self.side_has_lost(opponent) {
+                        outcome.result = MoveResult::Won;
+                        return outcome;
+                    }
+                } else if !player.keeps(index) {
+                    hand = player.take(index);
+                    record(
+                        trace,
+                        SowEvent::Pickup {
+                            index,
+                            stones: hand,
+                        },
+                    );
+                } else if placing {
+                    // a functional nyumba is taxed instead of emptied
+                    player.set(index, player.board_half[index] - NYUMBA_TAX);
+                    hand = NYUMBA_TAX;
+                    record(
+                        trace,
+                        SowEvent::Pickup {
+                            index,
+                            stones: hand,
+                        },
+                    );
+                }
+
+                if hand == 0 {
+                    break;
+                }
+                placing = false;
+
+                // the stones left in hand are not put back, an infinite move ends the game
+                outcome.laps += 1;
+                if outcome.laps > self.limits.lap_limit {
+                    outcome.result = MoveResult::Infinite;
+                    return outcome;
+                }
+            }
+
+            index = geometry.next_index(index, direction);
+            hand -= 1;
+            player.add(index, 1);
+            record(trace, SowEvent::Drop { index });
+        }
+
+        // check lose condition after move!
+        if self.side_has_lost(player) {
+            outcome.result = MoveResult::Lost;
+        }
+
+        outcome
+    }
+
+    fn has_lost(&self, position: &Position, _directions: &[Direction], player: Turn) -> bool {
+        self.side_has_lost(position.side(player))
+    }
+
+    fn has_namua(&self) -> bool {
+        self.namua
+    }
+
+    /// Rows need at least six bowls for the namua layout.
+    fn validate_rules(&self) -> Result<(), SetupError> {
+        if self.namua && self.geometry.bowls_per_row() < 6 {
+            return Err(SetupError::NamuaBoard(self.geometry.bowls_per_row()));
+        }
+
+        Ok(())
+    }
+
+    /// An owned nyumba needs the nyumba rule and stones.
+    fn validate(&self, position: &Position) -> Result<(), SetupError> {
+        for player in [Player1, Player2] {
+            let side = position.side(player);
+
+            if side.has_nyumba() && !self.nyumba {
+                return Err(SetupError::NyumbaWithoutRule(player));
+            }
+            if side.has_nyumba() && side.board_half[self.geometry.nyumba()] == 0 {
+                return Err(SetupError::EmptyNyumba(player));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    fn key(&self) -> u64 {
+        RulesKey::new("bao")
+            .with_geometry(self.geometry)
+            .with(self.capture_behind as u64)
+            .with(self.inner_row_loses as u64)
+            .with(self.direction_choice as u64)
+            .with(self.namua as u64)
+            .with(self.nyumba as u64)
+            .with(self.kichwa as u64)
+            .with(self.compulsory_capture as u64)
+            .with_limits(self.limits)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bao::tests::set_up;
+    use crate::bao::{Player, Setup};
+
+    /// The bowls of a side on a board of 2x8, the outer row first: `inner` at 8 and up.
+    fn board(outer: &[(usize, u8)], inner: &[(usize, u8)]) -> Vec<u8> {
+        let mut board = vec![0; 16];
+        for &(index, stones) in outer {
+            board[index] = stones;
+        }
+        for &(index, stones) in inner {
+            board[8 + index] = stones;
+        }
+        board
+    }
+
+    #[test]
+    fn setup_rejects_namua_on_short_rows() {
+        let rules = Bao {
+            geometry: Geometry::new(4, 2),
+            ..Bao::kiswahili()
+        };
+
+        let built = Setup::new(Direction::CW, rules)
+            .build(Player::new("Player 1", 0), Player::new("Player 2", 1))
+            .map(|_| ());
+
+        assert_eq!(built, Err(SetupError::NamuaBoard(4)));
+    }
+
+    #[test]
+    fn takasa_is_illegal_while_a_move_steals() {
+        let rules = Bao {
+            compulsory_capture: true,
+            ..Bao::normal()
+        };
+        // bowl 8 ends in bowl 10 facing two stones, bowl 12 ends in an empty bowl
+        let player1 = board(&[], &[(0, 2), (2, 1), (4, 2)]);
+        let player2 = board(&[], &[(5, 2)]);
+
+        let game = set_up(Setup::new(Direction::CCW, rules), &player1, &player2);
+
+        assert_eq!(game.legal_moves(), vec![Move::new(8, Direction::CCW)]);
+        assert_eq!(game.move_kind(Move::new(12, Direction::CCW)), None);
+
+        let game = set_up(
+            Setup::new(Direction::CCW, Bao::normal()),
+            &player1,
+            &player2,
+        );
+        assert_eq!(game.legal_moves().len(), 2);
+        assert_eq!(
+            game.move_kind(Move::new(12, Direction::CCW)),
+            Some(MoveKind::Takasa)
+        );
+        // an empty bowl can't be sown
+        assert_eq!(game.move_kind(Move::new(9, Direction::CCW)), None);
+    }
+
+    #[test]
+    fn takasa_sows_the_outer_row_only_if_the_inner_row_cannot_be() {
+        let rules = Bao {
+            compulsory_capture: true,
+            ..Bao::normal()
+        };
+        // nothing on the opponent's inner row to steal, nor behind it
+        let player2 = board(&[(0, 2)], &[]);
+
+        let player1 = board(&[(2, 2)], &[(4, 2)]);
+        let game = set_up(Setup::new(Direction::CCW, rules), &player1, &player2);
+        assert_eq!(game.legal_moves(), vec![Move::new(12, Direction::CCW)]);
+
+        let player1 = board(&[(2, 2)], &[(4, 1)]);
+        let game = set_up(Setup::new(Direction::CCW, rules), &player1, &player2);
+        assert_eq!(game.legal_moves(), vec![Move::new(2, Direction::CCW)]);
+    }
+
+    #[test]
+    fn nyumba_is_taxed_only_without_another_bowl_to_place_in() {
+        let rules = Bao {
+            namua: true,
+            nyumba: true,
+            compulsory_capture: true,
+            ..Bao::normal()
+        };
+        let nyumba = rules.geometry.nyumba();
+        let player2 = board(&[(0, 2)], &[]);
+        let setup = || {
+            Setup::new(Direction::CCW, rules)
+                .store(Player1, 20)
+                .store(Player2, 0)
+        };
+
+        let player1 = board(&[], &[(1, 1), (nyumba - 8, 6)]);
+        let game = set_up(setup(), &player1, &player2);
+        assert_eq!(game.legal_moves(), vec![Move::new(9, Direction::CCW)]);
+
+        let player1 = board(&[], &[(nyumba - 8, 6)]);
+        let game = set_up(setup(), &player1, &player2);
+        assert_eq!(game.legal_moves(), vec![Move::new(nyumba, Direction::CCW)]);
+    }
+
+    #[test]
+    fn sowing_ending_in_a_functional_nyumba_stops() {
+        // without kichwas every lap in the inner row would steal
+        let rules = Bao {
+            nyumba: true,
+            kichwa: true,
+            ..Bao::normal()
+        };
+        let player1 = board(&[], &[(1, 2), (3, 6)]);
+        let player2 = board(&[(0, 2)], &[]);
+        let mut game = set_up(Setup::new(Direction::CCW, rules), &player1, &player2);
+
+        let outcome = game.make_move(Move::new(9, Direction::CCW)).unwrap();
+
+        assert_eq!(outcome.laps, 1);
+        assert_eq!(
+            game.position().side(Player1).board(),
+            &board(&[], &[(2, 1), (3, 7)])[..]
+        );
+        assert!(game.position().side(Player1).has_nyumba());
+    }
+
+    #[test]
+    fn namua_takasa_on_a_functional_nyumba_takes_the_tax() {
+        let rules = Bao {
+            namua: true,
+            nyumba: true,
+            ..Bao::normal()
+        };
+        let nyumba = rules.geometry.nyumba();
+        let setup = Setup::new(Direction::CCW, rules)
+            .store(Player1, 20)
+            .store(Player2, 0);
+        let player1 = board(&[], &[(nyumba - 8, 6)]);
+        let player2 = board(&[(0, 2)], &[]);
+        let mut game = set_up(setup, &player1, &player2);
+
+        let outcome = game.make_move(Move::new(nyumba, Direction::CCW)).unwrap();
+
+        assert_eq!(outcome.kind, MoveKind::Takasa);
+        let side = game.position().side(Player1);
+        assert_eq!(side.board()[nyumba], 7 - NYUMBA_TAX);
+        assert_eq!(side.board()[nyumba + 1..nyumba + 3], [1, 1]);
+        assert_eq!(side.store(), 19);
+        assert!(side.has_nyumba());
+    }
+
+    #[test]
+    fn steal_from_the_nyumba_loses_it() {
+        let rules = Bao {
+            nyumba: true,
+            ..Bao::normal()
+        };
+        let nyumba = rules.geometry.nyumba();
+        // the lap ends in bowl 12, facing the opponent's nyumba
+        let player1 = board(&[], &[(2, 2), (4, 1)]);
+        let player2 = board(&[(0, 2)], &[(nyumba - 8, 6)]);
+        let mut game = set_up(Setup::new(Direction::CCW, rules), &player1, &player2);
+        assert!(game.position().side(Player2).has_nyumba());
+
+        let outcome = game.make_move(Move::new(10, Direction::CCW)).unwrap();
+
+        assert_eq!(outcome.steal, 6);
+        let side = game.position().side(Player2);
+        assert_eq!(side.board()[nyumba], 0);
+        assert!(!side.has_nyumba());
+    }
+
+    #[test]
+    fn kimbi_steal_is_sown_from_its_end_towards_the_middle() {
+        let rules = Bao {
+            kichwa: true,
+            ..Bao::normal()
+        };
+        // sown CW, the lap ends in bowl 9, a kimbi, facing one stone
+        let player1 = board(&[], &[(1, 1), (3, 2)]);
+        let player2 = board(&[(5, 2)], &[(6, 1)]);
+        let mut game = set_up(Setup::new(Direction::CW, rules), &player1, &player2);
+
+        let outcome = game.make_move(Move::new(11, Direction::CW)).unwrap();
+
+        assert_eq!(outcome.steal, 1);
+        // the stolen stone goes to the kichwa at bowl 8, the stealing bowl keeps its two
+        assert_eq!(
+            game.position().side(Player1).board(),
+            &board(&[], &[(0, 1), (1, 2), (2, 1)])[..]
+        );
+    }
+
+    #[test]
+    fn other_steals_keep_the_sowing_direction() {
+        let rules = Bao {
+            kichwa: true,
+            ..Bao::normal()
+        };
+        // sown CW, the lap ends in bowl 12 facing one stone
+        let player1 = board(&[], &[(4, 1), (6, 2)]);
+        let player2 = board(&[(5, 2)], &[(3, 1)]);
+        let mut game = set_up(Setup::new(Direction::CW, rules), &player1, &player2);
+
+        let outcome = game.make_move(Move::new(14, Direction::CW)).unwrap();
+
+        assert_eq!(outcome.steal, 1);
+        // the stolen stone goes on CW from the kichwa at bowl 15
+        assert_eq!(
+            game.position().side(Player1).board(),
+            &board(&[], &[(4, 2), (5, 1), (7, 1)])[..]
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use super::{Direction, Game, Player, RuleSet, Turn, Turn::*};
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+/// Builds a game from an arbitrary position instead of the starting layout.
+///
+/// Anything not set is taken from the starting position of the rules: the starting
+/// layout, player 1 to move on the first turn. A nyumba is owned if the rules start with
+/// one and it holds stones.
+pub struct Setup {
+    direction: Direction,
+    rules: Arc<dyn RuleSet>,
+    turn: Turn,
+    turn_count: usize,
+    boards: [Option<Vec<u8>>; 2],
+    stores: [Option<u8>; 2],
+    captured: [Option<u8>; 2],
+    nyumbas: [Option<bool>; 2],
+}
+
+/// Why a position can't be set up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SetupError {
+    /// The board of a player doesn't have the number of bowls of the geometry.
+    BoardSize { player: Turn, bowls: usize },
+    /// The board and the stores hold more stones than the set has.
+    TooManyStones { stones: usize, set: usize },
+    /// A player has stones in the store but the rules have no namua phase.
+    StoreWithoutNamua(Turn),
+    /// A player has captured stones but the rules put them back into play.
+    CapturedWithoutStore(Turn),
+    /// A player owns a nyumba but the rules have none.
+    NyumbaWithoutRule(Turn),
+    /// A player owns an empty nyumba, which is lost once emptied.
+    EmptyNyumba(Turn),
+    /// Turns are counted from 1 and can't exceed the turn limit.
+    TurnCount(usize),
+    /// The player has already lost, the game would be over.
+    GameOver(Turn),
+    /// The namua layout needs rows of at least six bowls, the rows have this many.
+    NamuaBoard(usize),
+    /// The set has more stones than a bowl can hold, this many.
+    SetTooLarge(usize),
+}
+
+impl fmt::Display for SetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetupError::BoardSize { player, bowls } => {
+                write!(f, "The board of {:?} needs {} bowls", player, bowls)
+            }
+            SetupError::TooManyStones { stones, set } => {
+                write!(f, "{} stones are more than the {} of the set", stones, set)
+            }
+            SetupError::StoreWithoutNamua(player) => {
+                write!(f, "{:?} has stones in the store without a namua", player)
+            }
+            SetupError::CapturedWithoutStore(player) => {
+                write!(
+                    f,
+                    "{:?} has captured stones but the rules keep none",
+                    player
+                )
+            }
+            SetupError::NyumbaWithoutRule(player) => {
+                write!(f, "{:?} owns a nyumba but the rules have none", player)
+            }
+            SetupError::EmptyNyumba(player) => write!(f, "{:?} owns an empty nyumba", player),
+            SetupError::TurnCount(turn_count) => write!(f, "Turn {} is out of range", turn_count),
+            SetupError::GameOver(player) => write!(f, "{:?} has already lost", player),
+            SetupError::NamuaBoard(bowls_per_row) => write!(
+                f,
+                "The namua layout needs rows of at least six bowls, not {}",
+                bowls_per_row
+            ),
+            SetupError::SetTooLarge(stones) => {
+                write!(f, "A set of {} stones doesn't fit into a bowl", stones)
+            }
+        }
+    }
+}
+
+impl Error for SetupError {}
+
+impl Setup {
+    pub fn new(direction: Direction, rules: impl RuleSet + 'static) -> Self {
+        Self {
+            direction,
+            rules: Arc::new(rules),
+            turn: Player1,
+            turn_count: 1,
+            boards: [None, None],
+            stores: [None, None],
+            captured: [None, None],
+            nyumbas: [None, None],
+        }
+    }
+
+    /// The bowls of `player`, indexed like `Side::board`.
+    pub fn board(mut self, player: Turn, bowls: &[u8]) -> Self {
+        self.boards[player as usize] = Some(bowls.to_vec());
+        self
+    }
+
+    /// The stones `player` still has to bring onto the board during namua.
+    pub fn store(mut self, player: Turn, stones: u8) -> Self {
+        self.stores[player as usize] = Some(stones);
+        self
+    }
+
+    /// The stones `player` has captured into the store.
+    pub fn captured(mut self, player: Turn, stones: u8) -> Self {
+        self.captured[player as usize] = Some(stones);
+        self
+    }
+
+    /// Whether `player` still owns the nyumba.
+    pub fn nyumba(mut self, player: Turn, owned: bool) -> Self {
+        self.nyumbas[player as usize] = Some(owned);
+        self
+    }
+
+    /// The player to move.
+    pub fn turn(mut self, turn: Turn) -> Self {
+        self.turn = turn;
+        self
+    }
+
+    pub fn turn_count(mut self, turn_count: usize) -> Self {
+        self.turn_count = turn_count;
+        self
+    }
+
+    /// Seats `player1` and `player2` at the position, if it is legal.
+    pub fn build(self, player1: Player, player2: Player) -> Result<Game, SetupError> {
+        let rules = self.rules;
+        let geometry = rules.geometry();
+        rules.validate_rules()?;
+
+        let mut game = Game::with_rules(self.direction, rules.clone(), player1, player2);
+        game.position.turn = self.turn;
+        game.turn_count = self.turn_count;
+
+        // the set has the stones of the starting position
+        let set = game.position.side(Player1).stones() + game.position.side(Player2).stones();
+
+        for turn in [Player1, Player2] {
+            let seat = turn as usize;
+            let player = game.position.side_mut(turn);
+
+            if let Some(board) = &self.boards[seat] {
+                if board.len() != geometry.bowls() {
+                    return Err(SetupError::BoardSize {
+                        player: turn,
+                        bowls: geometry.bowls(),
+                    });
+                }
+                player.board_mut().copy_from_slice(board);
+            }
+
+            if let Some(store) = self.stores[seat] {
+                if store > 0 && !rules.has_namua() {
+                    return Err(SetupError::StoreWithoutNamua(turn));
+                }
+                player.store = store;
+            }
+
+            if let Some(captured) = self.captured[seat] {
+                if captured > 0 && !rules.has_stores() {
+                    return Err(SetupError::CapturedWithoutStore(turn));
+                }
+                player.captured = captured;
+            }
+
+            player.nyumba = self.nyumbas[seat]
+                .unwrap_or(player.nyumba && player.board_half[geometry.nyumba()] > 0);
+        }
+
+        game.position.rekey();
+
+        let stones = game.position.side(Player1).stones() + game.position.side(Player2).stones();
+        if stones > set {
+            return Err(SetupError::TooManyStones { stones, set });
+        }
+
+        if game.turn_count == 0 || game.turn_count > rules.limits().turn_limit {
+            return Err(SetupError::TurnCount(game.turn_count));
+        }
+
+        rules.validate(&game.position)?;
+
+        for turn in [Player1, Player2] {
+            if rules.has_lost(&game.position, game.directions(), turn) {
+                return Err(SetupError::GameOver(turn));
+            }
+        }
+
+        Ok(game)
+    }
+}
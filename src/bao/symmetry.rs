@@ -0,0 +1,134 @@
+use super::{Direction, Game, Geometry, Move, Position, RuleSet, Turn::*};
+
+/// A position in canonical form, together with what it takes to map moves between it and
+/// the position it came from.
+///
+/// A position sown CW is the mirror image of one sown CCW. Under rules that are mirror
+/// symmetric, every position of a game sown CW is mirrored into one sown CCW. If each
+/// move picks its direction, the position and its mirror image are the same game and the
+/// one with the smaller key is canonical. Positions under other rules are canonical as
+/// they are.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Canonical {
+    pub position: Position,
+    /// The direction of the game in canonical form.
+    pub direction: Direction,
+    /// Whether `position` is the mirror image of the original position.
+    pub mirrored: bool,
+}
+
+impl Canonical {
+    /// Maps a move of the original position to the canonical position.
+    pub fn canonical_move(&self, mv: Move) -> Move {
+        if self.mirrored {
+            mv.mirror(&self.position.current().geometry)
+        } else {
+            mv
+        }
+    }
+
+    /// Maps a move of the canonical position back to the original position.
+    pub fn original_move(&self, mv: Move) -> Move {
+        // mirroring is its own inverse
+        self.canonical_move(mv)
+    }
+}
+
+impl Move {
+    /// The move sowing the mirrored bowl the other way.
+    pub fn mirror(self, geometry: &Geometry) -> Move {
+        Move::new(geometry.mirror(self.index), self.direction.reverse())
+    }
+}
+
+impl Position {
+    /// The position with the bowls of both sides swapped left to right.
+    pub fn mirror(&self) -> Position {
+        let mut position = *self;
+
+        for player in [Player1, Player2] {
+            let side = self.side(player);
+            let geometry = side.geometry;
+            let mirrored = position.side_mut(player);
+            for index in 0..geometry.bowls() {
+                mirrored.board_half[geometry.mirror(index)] = side.board_half[index];
+            }
+        }
+        position.rekey();
+
+        position
+    }
+
+    /// The canonical form of the position in a game sown in `direction` under `rules`.
+    pub fn canonical<R: RuleSet + ?Sized>(&self, rules: &R, direction: Direction) -> Canonical {
+        let original = Canonical {
+            position: *self,
+            direction,
+            mirrored: false,
+        };
+
+        if !rules.mirror_symmetric() {
+            return original;
+        }
+
+        let mirrored = Canonical {
+            position: self.mirror(),
+            direction: direction.reverse(),
+            mirrored: true,
+        };
+
+        let mirror = if rules.direction_choice() {
+            mirrored.position.key() < self.key()
+        } else {
+            direction == Direction::CW
+        };
+
+        if mirror {
+            mirrored
+        } else {
+            original
+        }
+    }
+}
+
+impl Game {
+    /// The canonical form of the position of the game.
+    pub fn canonical(&self) -> Canonical {
+        self.position.canonical(&*self.rules, self.direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bao::tests::random_moves;
+
+    #[test]
+    fn moves_map_to_the_canonical_position_and_back() {
+        random_moves(20, |game, mv| {
+            let rules = game.rules();
+            let canonical = game.canonical();
+            let canonical_mv = canonical.canonical_move(mv);
+            assert_eq!(canonical.original_move(canonical_mv), mv);
+
+            let directions = if rules.direction_choice() {
+                &Direction::ALL[..]
+            } else {
+                std::slice::from_ref(&canonical.direction)
+            };
+            assert!(rules.is_legal_move(&canonical.position, directions, canonical_mv));
+
+            // the move leads to the mirror image of where the canonical move leads
+            let (after, outcome) = game.position().apply(rules, mv);
+            let (canonical_after, canonical_outcome) =
+                canonical.position.apply(rules, canonical_mv);
+            let after = if canonical.mirrored {
+                after.mirror()
+            } else {
+                after
+            };
+            assert_eq!(outcome, canonical_outcome);
+            assert_eq!(after, canonical_after);
+        });
+    }
+}
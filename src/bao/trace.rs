@@ -0,0 +1,38 @@
+use super::{Move, MoveResult};
+
+/// A single step of a move, in the order it happened. Indices are bowls of the player who
+/// moved, except for feeds and captures, which name bowls of the opponent.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SowEvent {
+    /// A stone from the store is placed during namua.
+    Place { index: usize },
+    /// The stones of a bowl are taken into the hand: the bowl the move starts from, a
+    /// relay or a taxed nyumba.
+    Pickup { index: usize, stones: u8 },
+    /// A stone is dropped.
+    Drop { index: usize },
+    /// A stone is dropped into the store of the player, in games sown through it.
+    Store,
+    /// A stone is dropped into a bowl of the opponent, in games sown around the whole
+    /// board.
+    Feed { index: usize },
+    /// The stones of an opponent's bowl are stolen.
+    Capture { index: usize, stones: u8 },
+    /// The game was decided here, the rest of the move is not sown.
+    Decided(MoveResult),
+}
+
+/// A move and every step it took.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveTrace {
+    pub mv: Move,
+    pub events: Vec<SowEvent>,
+}
+
+/// Records `event` if the move is traced.
+#[inline(always)]
+pub(super) fn record(trace: &mut Option<&mut Vec<SowEvent>>, event: SowEvent) {
+    if let Some(events) = trace {
+        events.push(event);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Agent, Game, Turn::*};
+use super::{Agent, Game, Geometry, Move};
 
 use std::iter;
 
@@ -6,67 +6,78 @@ use radiate::Neat;
 
 pub struct RadiateAgent<'o> {
     model: &'o mut Neat,
-    input: [f32; 33],
+    input: Vec<f32>,
     output: Vec<f32>,
-    indexed_output: [(usize, f32); 16],
+    /// The outputs for each direction we may sow in, one after the other.
+    scores: Vec<f32>,
 }
 
 impl<'o> RadiateAgent<'o> {
     pub fn new(model: &'o mut Neat) -> Self {
         Self {
             model,
-            input: [0.0; 33],
-            output: vec![0.0; 16],
-            indexed_output: [(0, 0.0); 16],
+            input: Vec::new(),
+            output: Vec::new(),
+            scores: Vec::new(),
         }
     }
+
+    /// The inputs of a net playing on `geometry`: the bowls of both players and the
+    /// direction.
+    pub fn input_size(geometry: &Geometry) -> usize {
+        2 * geometry.bowls() + 1
+    }
+
+    /// The outputs of a net playing on `geometry`: a score for each bowl of the player.
+    pub fn output_size(geometry: &Geometry) -> usize {
+        geometry.bowls()
+    }
+
+    /// Whether `model` has the inputs and outputs of a net playing on `geometry`.
+    pub fn fits(model: &Neat, geometry: &Geometry) -> bool {
+        model.input_size as usize == Self::input_size(geometry)
+            && model.layers.last().map(|wrap| wrap.layer.shape().1)
+                == Some(Self::output_size(geometry))
+    }
 }
 
 impl Agent for RadiateAgent<'_> {
-    fn pick_index(&mut self, game: &Game) -> usize {
-        let (player, opponent) = if game.turn() == Player1 {
-            (&game.player1, &game.player2)
-        } else {
-            (&game.player2, &game.player1)
-        };
+    fn pick_move(&mut self, game: &Game) -> Move {
+        let position = game.position();
+        let (player, opponent) = (position.current(), position.opponent());
 
-        for (src, dst) in player
-            .board_half
-            .iter()
-            .chain(opponent.board_half.iter())
-            .map(|&val| val as f32)
-            .chain(iter::once(game.direction.input_enc()))
-            .zip(&mut self.input[..])
-        {
-            *dst = src;
-        }
+        self.scores.clear();
+
+        // The direction is an input of the net, so ask it once per direction we may sow in.
+        for &direction in game.directions() {
+            self.input.clear();
+            self.input.extend(
+                player
+                    .board()
+                    .iter()
+                    .chain(opponent.board())
+                    .map(|&val| val as f32)
+                    .chain(iter::once(direction.input_enc())),
+            );
 
-        self.output = self
-            .model
-            .forward(&self.input.to_vec())
-            .expect("No output?");
+            self.output = self.model.forward(&self.input).expect("No output?");
 
-        for (src, dst) in self
-            .output
-            .iter()
-            .copied()
-            .enumerate()
-            .zip(&mut self.indexed_output)
-        {
-            *dst = src;
+            self.scores.extend(&self.output);
         }
 
-        self.indexed_output
-            .sort_unstable_by(|(_, a), (_, b)| b.partial_cmp(a).expect("NaN?"));
+        // Select the best move that is valid.
+        let directions = game.directions();
+        let bowls = game.geometry().bowls();
+        let score = |mv: &Move| {
+            let direction = directions
+                .iter()
+                .position(|&direction| direction == mv.direction)
+                .expect("Invalid direction?");
+            self.scores[direction * bowls + mv.index]
+        };
 
-        // Select the best index that is valid.
-        let index = self
-            .indexed_output
-            .iter()
-            .find(|&&(index, _)| player.is_valid_index(index))
+        game.legal_moves_iter()
+            .max_by(|a, b| score(a).partial_cmp(&score(b)).expect("NaN?"))
             .expect("No valid index?")
-            .0;
-
-        index
     }
 }
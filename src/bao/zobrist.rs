@@ -0,0 +1,137 @@
+use super::{Geometry, Limits, MAX_BOWLS};
+
+/// Random 64-bit keys for every part of a game, xored together into the key of a
+/// position or a game.
+///
+/// The keys are generated at compile time from a fixed seed, so keys are the same across
+/// runs and can be stored in opening books or training data.
+pub(super) struct Keys {
+    /// By seat, bowl and stones in it. An empty bowl has no key.
+    pub(super) bowls: [[[u64; 256]; MAX_BOWLS]; 2],
+    /// By seat and stones in the store.
+    pub(super) stores: [[u64; 256]; 2],
+    /// By seat and stones captured.
+    pub(super) captured: [[u64; 256]; 2],
+    /// By seat, while the nyumba is owned.
+    pub(super) nyumbas: [u64; 2],
+    /// Player 2 to move.
+    pub(super) turn: u64,
+    pub(super) directions: [u64; 2],
+}
+
+pub(super) static KEYS: Keys = Keys::new();
+
+/// The next state and output of splitmix64.
+const fn split_mix(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (state, z ^ (z >> 31))
+}
+
+impl Keys {
+    const fn new() -> Self {
+        let mut keys = Keys {
+            bowls: [[[0; 256]; MAX_BOWLS]; 2],
+            stores: [[0; 256]; 2],
+            captured: [[0; 256]; 2],
+            nyumbas: [0; 2],
+            turn: 0,
+            directions: [0; 2],
+        };
+        let mut state = 0x6261_6f5f_7a6f_6272;
+        let mut key;
+
+        let mut seat = 0;
+        while seat < 2 {
+            let mut index = 0;
+            while index < MAX_BOWLS {
+                let mut stones = 1;
+                while stones < 256 {
+                    (state, key) = split_mix(state);
+                    keys.bowls[seat][index][stones] = key;
+                    stones += 1;
+                }
+                index += 1;
+            }
+
+            let mut stones = 1;
+            while stones < 256 {
+                (state, key) = split_mix(state);
+                keys.stores[seat][stones] = key;
+                (state, key) = split_mix(state);
+                keys.captured[seat][stones] = key;
+                stones += 1;
+            }
+
+            (state, key) = split_mix(state);
+            keys.nyumbas[seat] = key;
+            seat += 1;
+        }
+
+        let mut direction = 0;
+        while direction < 2 {
+            (state, key) = split_mix(state);
+            keys.directions[direction] = key;
+            direction += 1;
+        }
+
+        (_, key) = split_mix(state);
+        keys.turn = key;
+
+        keys
+    }
+}
+
+/// The key of a ruleset, the FNV-1a hash of its name and options. Each option is hashed
+/// as a number in a fixed order, so the key only changes when the rules do.
+pub(super) struct RulesKey(u64);
+
+impl RulesKey {
+    pub(super) fn new(name: &str) -> Self {
+        Self(0xcbf2_9ce4_8422_2325).bytes(name.as_bytes())
+    }
+
+    fn bytes(self, bytes: &[u8]) -> Self {
+        Self(bytes.iter().fold(self.0, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        }))
+    }
+
+    pub(super) fn with(self, option: u64) -> Self {
+        self.bytes(&option.to_le_bytes())
+    }
+
+    pub(super) fn with_geometry(self, geometry: Geometry) -> Self {
+        self.with(geometry.bowls_per_row() as u64)
+            .with(geometry.rows() as u64)
+    }
+
+    pub(super) fn with_limits(self, limits: Limits) -> Self {
+        self.with(limits.lap_limit as u64)
+            .with(limits.infinite_move as u64)
+            .with(limits.turn_limit as u64)
+            .with(limits.tie_break as u64)
+    }
+
+    pub(super) fn finish(self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bao::tests::random_moves;
+
+    #[test]
+    fn incremental_keys_match_keys_from_scratch() {
+        random_moves(20, |game, mv| {
+            let (after, _) = game.position().apply(game.rules(), mv);
+            let mut rekeyed = after;
+            rekeyed.rekey();
+
+            assert_eq!(after.key(), rekeyed.key());
+        });
+    }
+}
@@ -0,0 +1,3 @@
+mod bao;
+
+pub use crate::bao::*;
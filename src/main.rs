@@ -1,55 +1,93 @@
-mod bao;
-use bao::{Direction, Game, HumanAgent, MaximizeAgent, Mode, Player, RadiateAgent, RandomAgent};
+use bao::{
+    Bao, Direction, Game, GameResult, HumanAgent, Hus, Kalah, Omweso, Oware, Player, RadiateAgent,
+    RandomAgent, RuleSet,
+};
 
 use radiate::prelude::*;
 use radiate::{Neat, NeatEnvironment, Problem};
 
 use std::fs::OpenOptions;
 use std::io::prelude::*;
+use std::sync::Arc;
+
+/// The rules named by the second argument, easy Bao if there is none.
+fn rules() -> Arc<dyn RuleSet> {
+    match std::env::args().nth(2).as_deref() {
+        None | Some("easy") => Arc::new(Bao::easy()),
+        Some("normal") => Arc::new(Bao::normal()),
+        Some("kiswahili") => Arc::new(Bao::kiswahili()),
+        Some("omweso") => Arc::new(Omweso::default()),
+        Some("hus") => Arc::new(Hus::default()),
+        Some("oware") => Arc::new(Oware::default()),
+        Some("kalah") => Arc::new(Kalah::default()),
+        Some(other) => panic!(
+            "Unknown rules {}, try easy, normal, kiswahili, omweso, hus, oware or kalah",
+            other
+        ),
+    }
+}
 
 fn random_ai_game() {
     let mut neat = Neat::load("radiate_ai_v_ai.json").expect("Could not load ai file");
+    assert!(
+        RadiateAgent::fits(&neat, &rules().geometry()),
+        "radiate_ai_v_ai.json was trained on another board, train it on these rules first"
+    );
 
     let mut results = [0; 2];
+    let mut draws = 0;
     for _ in 0..100000 {
-        let winner_tag = Game::new(
+        let result = Game::with_rules(
             Direction::CW,
-            Mode::Easy,
+            rules(),
             Player::new("Player 1", 0),
             Player::new("Player 2", 1),
         )
-        .play(&mut RandomAgent::default(), &mut RadiateAgent::new(&mut neat))
-        .winner
-        .tag();
+        .play(&mut RandomAgent, &mut RadiateAgent::new(&mut neat))
+        .expect("Game aborted on an illegal move");
 
-        results[winner_tag] += 1;
+        match result.winner() {
+            Some(winner) => results[winner.tag()] += 1,
+            None => draws += 1,
+        }
     }
 
     println!("First Player: {}", results[0]);
     println!("Second Player: {}", results[1]);
+    println!("Draws: {}", draws);
 }
 
 fn human_game() {
-    let winner = Game::new(
+    let result = Game::with_rules(
         Direction::CW,
-        Mode::Easy,
+        rules(),
         Player::new("Player 1", 0),
         Player::new("Player 2", 1),
     )
-    .play(&mut HumanAgent::default(), &mut RandomAgent::default())
-    .winner;
+    .play(&mut HumanAgent, &mut RandomAgent)
+    .expect("Game aborted on an illegal move");
+
+    match result.winner() {
+        Some(winner) => println!("Winner: {}", winner.name()),
+        None => println!("Draw"),
+    }
+}
 
-    println!("Winner: {}", winner.name());
+/// The fitness of the player with `tag` in a single game: 1 for a win, -1 for a loss
+/// and 0 for a draw.
+fn score(result: &GameResult, tag: usize) -> f32 {
+    match result.winner() {
+        Some(winner) if winner.tag() == tag => 1.0,
+        Some(_) => -1.0,
+        None => 0.0,
+    }
 }
 
-impl Problem<Neat> for Game {
+struct Training;
+
+impl Problem<Neat> for Training {
     fn empty() -> Self {
-        Game::new(
-            Direction::CW,
-            Mode::Easy,
-            Player::new("Player 1", 0),
-            Player::new("Player 2", 1),
-        )
+        Training
     }
 
     fn solve(&self, member: &mut Neat) -> f32 {
@@ -62,7 +100,7 @@ impl Problem<Neat> for Game {
             let mut radiate_agent = RadiateAgent::new(member);
             // let result = Game::new(
             //     Direction::CW,
-            //     Mode::Easy,
+            //     Bao::easy(),
             //     Player::new("Player 1", 0),
             //     Player::new("Player 2", 1),
             // )
@@ -73,7 +111,7 @@ impl Problem<Neat> for Game {
             // fitness += if result.winner.tag() == 1 { 1.0 } else { -1.0 };
             // let result = Game::new(
             //     Direction::CW,
-            //     Mode::Easy,
+            //     Bao::easy(),
             //     Player::new("Player 1", 0),
             //     Player::new("Player 2", 1),
             // )
@@ -83,10 +121,9 @@ impl Problem<Neat> for Game {
             // //  println!("=================");
             // fitness += if result.winner.tag() == 0 { 1.0 } else { -1.0 };
 
-
             // let result = Game::new(
             //     Direction::CW,
-            //     Mode::Easy,
+            //     Bao::easy(),
             //     Player::new("Player 1", 0),
             //     Player::new("Player 2", 1),
             // )
@@ -97,7 +134,7 @@ impl Problem<Neat> for Game {
             // fitness += if result.winner.tag() == 1 { 1.0 } else { -1.0 };
             // let result = Game::new(
             //     Direction::CW,
-            //     Mode::Easy,
+            //     Bao::easy(),
             //     Player::new("Player 1", 0),
             //     Player::new("Player 2", 1),
             // )
@@ -107,39 +144,40 @@ impl Problem<Neat> for Game {
             // // println!("=================");
             // fitness += if result.winner.tag() == 0 { 1.0 } else { -1.0 };
 
-
-
-            let result = Game::new(
+            let result = Game::with_rules(
                 Direction::CW,
-                Mode::Easy,
+                rules(),
                 Player::new("Player 1", 0),
                 Player::new("Player 2", 1),
             )
-            .play(&mut RandomAgent::default(), &mut radiate_agent);
+            .play(&mut RandomAgent, &mut radiate_agent)
+            .expect("Game aborted on an illegal move");
             // println!("{:?} won!", result.winner);
             // println!("{:?} lost!", result.loser);
             // println!("=================");
-            fitness += if result.winner.tag() == 1 { 1.0 } else { -1.0 };
-            let result = Game::new(
+            fitness += score(&result, 1);
+            let result = Game::with_rules(
                 Direction::CW,
-                Mode::Easy,
+                rules(),
                 Player::new("Player 1", 0),
                 Player::new("Player 2", 1),
             )
-            .play(&mut radiate_agent, &mut RandomAgent::default());
+            .play(&mut radiate_agent, &mut RandomAgent)
+            .expect("Game aborted on an illegal move");
             // println!("{:?} won!", result.winner);
             // println!("{:?} lost!", result.loser);
             // println!("=================");
-            fitness += if result.winner.tag() == 0 { 1.0 } else { -1.0 };
+            fitness += score(&result, 0);
         }
         fitness / (runs * 2) as f32
     }
 }
 
 fn train_radiate() {
+    let geometry = rules().geometry();
     let mut neat_env = NeatEnvironment::new()
-        .set_input_size(33)
-        .set_output_size(16)
+        .set_input_size(RadiateAgent::input_size(&geometry) as u32)
+        .set_output_size(RadiateAgent::output_size(&geometry) as u32)
         .set_weight_mutate_rate(0.5)
         .set_edit_weights(0.5)
         .set_weight_perturb(1.0)
@@ -152,12 +190,16 @@ fn train_radiate() {
             Activation::Sigmoid,
         ]);
 
-    let mut neat = Neat::load("radiate_ai_final3.json").expect("Could not load ai file");
-
+    // The saved net was trained on the 8x2 board, other boards start from an untrained net.
+    let neat = Neat::load("radiate_ai_final3.json").expect("Could not load ai file");
+    let neat = if RadiateAgent::fits(&neat, &geometry) {
+        neat
+    } else {
+        Neat::base(&mut neat_env)
+    };
 
     let target_gen = 5000;
-    let starting_net = Neat::base(&mut neat_env);
-    let (solution, _) = radiate::Population::<Neat, NeatEnvironment, Game>::new()
+    let (solution, _) = radiate::Population::<Neat, NeatEnvironment, Training>::new()
         .constrain(neat_env)
         .size(1000)
         .populate_clone(neat)